@@ -1,4 +1,6 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
 pub enum Message {
     Select,
@@ -8,11 +10,34 @@ pub enum Message {
     Right,
     Quit,
     Reset,
+    Undo,
+    Redo,
+    Save,
+    Hint,
+    /// Offers a draw to the network opponent, or accepts theirs.
+    Draw,
+    Resign,
+    /// Starts writing a chat message to the network opponent.
+    Chat,
+    /// Switches between a turn of the base rules and one using a god power.
+    Power,
+    /// Left click on the terminal cell at `column` and `row`.
+    Click {
+        column: u16,
+        row: u16,
+    },
 }
 
 pub fn handle_event() -> Option<Message> {
-    let Ok(Event::Key(KeyEvent { code, .. })) = event::read() else {
-        return None;
+    let code = match event::read() {
+        Ok(Event::Key(KeyEvent { code, .. })) => code,
+        Ok(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            ..
+        })) => return Some(Message::Click { column, row }),
+        _ => return None,
     };
 
     match code {
@@ -23,6 +48,60 @@ pub fn handle_event() -> Option<Message> {
         KeyCode::Char('l') | KeyCode::Right => Some(Message::Right),
         KeyCode::Enter | KeyCode::Char(' ') => Some(Message::Select),
         KeyCode::Esc => Some(Message::Reset),
+        KeyCode::Char('u') => Some(Message::Undo),
+        KeyCode::Char('r') => Some(Message::Redo),
+        KeyCode::Char('s') => Some(Message::Save),
+        KeyCode::Char('a') => Some(Message::Hint),
+        KeyCode::Char('d') => Some(Message::Draw),
+        KeyCode::Char('x') => Some(Message::Resign),
+        KeyCode::Char('c') => Some(Message::Chat),
+        KeyCode::Char('p') => Some(Message::Power),
         _ => None,
     }
 }
+
+/// A key pressed while writing a chat message.
+pub enum TextInput {
+    Char(char),
+    Backspace,
+    Send,
+    Cancel,
+}
+
+/// Like `poll_event`, for keys typing text.
+pub fn poll_text(timeout: Duration) -> Option<TextInput> {
+    if !event::poll(timeout).unwrap_or(false) {
+        return None;
+    }
+    let Ok(Event::Key(KeyEvent { code, .. })) = event::read() else {
+        return None;
+    };
+    match code {
+        KeyCode::Char(c) => Some(TextInput::Char(c)),
+        KeyCode::Backspace => Some(TextInput::Backspace),
+        KeyCode::Enter => Some(TextInput::Send),
+        KeyCode::Esc => Some(TextInput::Cancel),
+        _ => None,
+    }
+}
+
+/// Like `handle_event`, but returns `None` when no event came within `timeout`.
+pub fn poll_event(timeout: Duration) -> Option<Message> {
+    if !event::poll(timeout).unwrap_or(false) {
+        return None;
+    }
+    handle_event()
+}
+
+/// Waits up to `timeout` for any key or click, returning whether one came.
+pub fn poll_key(timeout: Duration) -> bool {
+    event::poll(timeout).unwrap_or(false)
+        && matches!(
+            event::read(),
+            Ok(Event::Key(_)
+                | Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(_),
+                    ..
+                }))
+        )
+}
@@ -1,14 +1,68 @@
-use super::*;
+use super::{
+    movegen::{Encoder, Move, Moves},
+    *,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Board {
     pub player1_meeple: PositionSet,
     pub player2_meeple: PositionSet,
-    pub first_floor: PositionSet,
-    pub second_floor: PositionSet,
-    pub third_floor: PositionSet,
-    pub dome: PositionSet,
+    pub player3_meeple: PositionSet,
+    pub player4_meeple: PositionSet,
+    /// Tiles built to at least the first, second and third level, then domes.
+    /// A dome covers all four planes whatever level it was built on, so the
+    /// level of a tile is the number of planes holding it.
+    pub levels: [PositionSet; 4],
     pub next_player: Player,
+    pub player1_god: Option<God>,
+    pub player2_god: Option<God>,
+    pub player3_god: Option<God>,
+    pub player4_god: Option<God>,
+    /// Two or three players each on their own, or four playing in teams of
+    /// `Player1` and `Player3` against `Player2` and `Player4`.
+    pub player_count: usize,
+    /// Players who could not move, their workers were removed from the board.
+    pub eliminated: [bool; 4],
+    /// Set when Athena moved up during her last turn, until her next one.
+    pub athena_moved_up: bool,
+    /// Set by the turn that won the game, after which no turn is possible.
+    pub won: Option<Outcome>,
+    pub size: Size,
+    /// Pieces left to build with, unlimited when `None`.
+    pub supply: Option<Supply>,
+    /// Zobrist hash of the position, updated by every move. Call `rehash` after
+    /// changing the other fields directly.
+    pub hash: u64,
+}
+
+/// What a turn changed on a board, returned by `Board::apply` to take the
+/// turn back with `Board::undo`.
+#[derive(Clone, Copy, Debug)]
+pub struct Undo {
+    // Eliminations and pushes can move the workers of any player.
+    meeple: [PositionSet; 4],
+    // Tiles built on by the turn, with their level before it.
+    levels: [Option<(Position, Construction)>; 2],
+    next_player: Player,
+    eliminated: [bool; 4],
+    athena_moved_up: bool,
+    won: Option<Outcome>,
+    supply: Option<Supply>,
+    hash: u64,
+}
+
+impl Undo {
+    fn save_level(&mut self, position: Position, level: Construction) {
+        if let Some(slot) = self.levels.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some((position, level));
+        }
+    }
+}
+
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
 }
 
 impl Board {
@@ -16,36 +70,209 @@ impl Board {
         Self {
             player1_meeple: PositionSet::new(),
             player2_meeple: PositionSet::new(),
-            first_floor: PositionSet::new(),
-            second_floor: PositionSet::new(),
-            third_floor: PositionSet::new(),
-            dome: PositionSet::new(),
+            player3_meeple: PositionSet::new(),
+            player4_meeple: PositionSet::new(),
+            levels: [PositionSet::new(); 4],
             next_player: Player::Player1,
+            player1_god: None,
+            player2_god: None,
+            player3_god: None,
+            player4_god: None,
+            player_count: 2,
+            eliminated: [false; 4],
+            athena_moved_up: false,
+            won: None,
+            size: Size::default(),
+            supply: None,
+            hash: 0,
+        }
+    }
+
+    pub fn with_gods(player1: Option<God>, player2: Option<God>) -> Self {
+        Self::with_players(&[player1, player2])
+    }
+
+    /// A board for as many players as `gods`, from two to four.
+    pub fn with_players(gods: &[Option<God>]) -> Self {
+        let mut board = Self {
+            player_count: gods.len(),
+            ..Self::new()
+        };
+        for (player, &god) in Player::ALL.into_iter().zip(gods) {
+            *board.get_player_god_mut(player) = god;
+        }
+        board.rehash();
+        board
+    }
+
+    pub fn rehash(&mut self) {
+        self.hash = zobrist::hash(self);
+    }
+
+    /// The board mirrored or rotated by `symmetry`, which must preserve its size.
+    pub fn transform(&self, symmetry: Symmetry) -> Board {
+        let transform = |set: PositionSet| set.transform(symmetry, self.size);
+        let mut board = Board {
+            player1_meeple: transform(self.player1_meeple),
+            player2_meeple: transform(self.player2_meeple),
+            player3_meeple: transform(self.player3_meeple),
+            player4_meeple: transform(self.player4_meeple),
+            levels: self.levels.map(transform),
+            ..self.clone()
+        };
+        board.rehash();
+        board
+    }
+
+    /// The smallest of the boards symmetric to this one, with the symmetry
+    /// giving it. Workers of a player are interchangeable already.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .filter(|symmetry| symmetry.preserves(self.size))
+            .map(|symmetry| (self.transform(symmetry), symmetry))
+            .min_by_key(|(board, _)| board.symmetry_key())
+            .unwrap()
+    }
+
+    /// Symmetries leaving the board unchanged, the identity included.
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::ALL
+            .into_iter()
+            .filter(|&symmetry| {
+                symmetry.preserves(self.size)
+                    && self.transform(symmetry).symmetry_key() == self.symmetry_key()
+            })
+            .collect()
+    }
+
+    // Orders the boards symmetric to each other.
+    fn symmetry_key(&self) -> ([PositionSet; 4], [PositionSet; 4]) {
+        (
+            self.levels,
+            Player::ALL.map(|player| self.get_player_meeple(player)),
+        )
+    }
+
+    fn switch_player(&mut self) {
+        let next = self.player_after(self.next_player);
+        self.hash ^= zobrist::to_move(self.next_player) ^ zobrist::to_move(next);
+        self.next_player = next;
+    }
+
+    /// Players taking part in the game, in turn order.
+    pub fn players(&self) -> impl Iterator<Item = Player> + use<> {
+        Player::ALL.into_iter().take(self.player_count)
+    }
+
+    fn remaining_players(&self) -> impl Iterator<Item = Player> + '_ {
+        self.players()
+            .filter(|&player| !self.eliminated[player as usize])
+    }
+
+    // The next player still in the game after `player`.
+    fn player_after(&self, player: Player) -> Player {
+        (1..=self.player_count)
+            .map(|offset| Player::ALL[(player as usize + offset) % self.player_count])
+            .find(|&next| !self.eliminated[next as usize])
+            .unwrap()
+    }
+
+    /// The player who played the previous turn, skipping eliminated players.
+    pub fn previous_player(&self) -> Player {
+        (1..=self.player_count)
+            .map(|offset| {
+                Player::ALL
+                    [(self.next_player as usize + self.player_count - offset) % self.player_count]
+            })
+            .find(|&previous| !self.eliminated[previous as usize])
+            .unwrap()
+    }
+
+    /// Whether `player` and `other` win or lose together.
+    pub fn same_side(&self, player: Player, other: Player) -> bool {
+        player == other || (self.player_count == 4 && player as usize % 2 == other as usize % 2)
+    }
+
+    fn teammate(&self, player: Player) -> Option<Player> {
+        (self.player_count == 4).then(|| Player::ALL[(player as usize + 2) % 4])
+    }
+
+    fn victory(&self, winner: Player, reason: WinReason) -> Outcome {
+        Outcome {
+            winner,
+            teammate: self.teammate(winner),
+            reason,
         }
     }
 
+    /// Workers of the players playing against `player`.
+    pub fn get_opponents_meeple(&self, player: Player) -> PositionSet {
+        self.players()
+            .filter(|&other| !self.same_side(player, other))
+            .fold(PositionSet::new(), |acc, other| {
+                acc.union(self.get_player_meeple(other))
+            })
+    }
+
+    // Athena's power keeps her opponents from moving up after she did.
+    fn no_move_up(&self) -> bool {
+        self.athena_moved_up
+            && !self.players().any(|player| {
+                self.get_player_god(player) == Some(God::Athena)
+                    && self.same_side(player, self.next_player)
+            })
+    }
+
+    fn set_athena_moved_up(&mut self, athena_moved_up: bool) {
+        if self.athena_moved_up != athena_moved_up {
+            self.hash ^= zobrist::ATHENA_MOVED_UP;
+        }
+        self.athena_moved_up = athena_moved_up;
+    }
+
     fn get_construction(&self, position: Position) -> Construction {
-        if self.first_floor.contains(position) {
-            Construction::FirstLevel
-        } else if self.second_floor.contains(position) {
-            Construction::SecondLevel
-        } else if self.third_floor.contains(position) {
-            Construction::ThirdLevel
-        } else if self.dome.contains(position) {
-            Construction::Dome
-        } else {
-            Construction::GroundLevel
+        let height = self
+            .levels
+            .iter()
+            .take_while(|plane| plane.contains(position))
+            .count();
+        [
+            Construction::GroundLevel,
+            Construction::FirstLevel,
+            Construction::SecondLevel,
+            Construction::ThirdLevel,
+            Construction::Dome,
+        ][height]
+    }
+
+    /// Tiles built to `level` or higher.
+    pub fn at_least(&self, level: Construction) -> PositionSet {
+        match level {
+            Construction::GroundLevel => self.size.positions(),
+            level => self.levels[level as usize - 1],
         }
     }
 
-    pub fn get_tile(&self, position: Position) -> Tile {
-        let player = if self.player1_meeple.contains(position) {
-            Some(Player::Player1)
-        } else if self.player2_meeple.contains(position) {
-            Some(Player::Player2)
-        } else {
-            None
+    pub fn domes(&self) -> PositionSet {
+        self.levels[3]
+    }
+
+    // Tiles a worker on `level` can move to, at most one level higher unless
+    // it may not move up at all. As in `Construction::can_move`, only workers
+    // on the second level reach the third.
+    fn climbable(&self, level: Construction, no_move_up: bool) -> PositionSet {
+        let highest = match level {
+            Construction::ThirdLevel => Construction::SecondLevel as usize,
+            level => level as usize + usize::from(!no_move_up),
         };
+        self.size.positions().difference(self.levels[highest])
+    }
+
+    pub fn get_tile(&self, position: Position) -> Tile {
+        let player = Player::ALL
+            .into_iter()
+            .find(|&player| self.get_player_meeple(player).contains(position));
 
         Tile {
             construction: self.get_construction(position),
@@ -57,6 +284,8 @@ impl Board {
         match player {
             Player::Player1 => &mut self.player1_meeple,
             Player::Player2 => &mut self.player2_meeple,
+            Player::Player3 => &mut self.player3_meeple,
+            Player::Player4 => &mut self.player4_meeple,
         }
     }
 
@@ -64,148 +293,931 @@ impl Board {
         match player {
             Player::Player1 => self.player1_meeple,
             Player::Player2 => self.player2_meeple,
+            Player::Player3 => self.player3_meeple,
+            Player::Player4 => self.player4_meeple,
+        }
+    }
+
+    pub fn get_player_god(&self, player: Player) -> Option<God> {
+        match player {
+            Player::Player1 => self.player1_god,
+            Player::Player2 => self.player2_god,
+            Player::Player3 => self.player3_god,
+            Player::Player4 => self.player4_god,
+        }
+    }
+
+    pub fn set_player_god(&mut self, player: Player, god: Option<God>) {
+        *self.get_player_god_mut(player) = god;
+        self.rehash();
+    }
+
+    fn get_player_god_mut(&mut self, player: Player) -> &mut Option<God> {
+        match player {
+            Player::Player1 => &mut self.player1_god,
+            Player::Player2 => &mut self.player2_god,
+            Player::Player3 => &mut self.player3_god,
+            Player::Player4 => &mut self.player4_god,
         }
     }
 
     fn get_meeple(&self) -> PositionSet {
-        self.player1_meeple.union(self.player2_meeple)
+        self.player1_meeple
+            .union(self.player2_meeple)
+            .union(self.player3_meeple)
+            .union(self.player4_meeple)
     }
 
     pub fn place_worker(&self, p1: Position, p2: Position) -> error::Result<Self> {
         let mut new_board = self.clone();
-        let other_player_meeple = new_board.get_player_meeple(self.next_player.other_player());
-        if other_player_meeple.contains(p1) || other_player_meeple.contains(p2) {
-            return Err(error::GameError::InvalidMove);
+        new_board.place(p1, p2)?;
+        Ok(new_board)
+    }
+
+    fn place(&mut self, p1: Position, p2: Position) -> error::Result<()> {
+        if p1 == p2 {
+            return Err(error::GameError::WorkersOverlap(p1));
+        }
+        if let Some(off_board) = [p1, p2]
+            .into_iter()
+            .find(|&p| !self.size.positions().contains(p))
+        {
+            return Err(error::GameError::OffBoard(off_board));
         }
-        let player_meeple = new_board.get_player_meeple_mut(self.next_player);
+        let placed = self.get_meeple();
+        if let Some(occupied) = [p1, p2].into_iter().find(|&p| placed.contains(p)) {
+            return Err(error::GameError::DestinationOccupied(occupied));
+        }
+        let player = self.next_player;
+        let player_meeple = self.get_player_meeple_mut(player);
         if !player_meeple.is_empty() {
-            return Err(error::GameError::InvalidMove);
+            return Err(error::GameError::SetupAlreadyDone);
         }
         *player_meeple = [p1, p2].into();
-        new_board.next_player = self.next_player.other_player();
-        Ok(new_board)
+        self.hash ^= zobrist::worker(p1, player) ^ zobrist::worker(p2, player);
+        self.switch_player();
+        Ok(())
     }
 
+    /// The board after `turn`, see `apply`.
     pub fn action(&self, turn: &turn::Turn) -> error::Result<Self> {
-        let (start, end, build) = match turn {
+        let mut new_board = self.clone();
+        new_board.apply(turn)?;
+        Ok(new_board)
+    }
+
+    /// Plays `turn` in place, returning what `undo` needs to take it back. The
+    /// board is left unchanged when the turn is illegal.
+    pub fn apply(&mut self, turn: &turn::Turn) -> error::Result<Undo> {
+        if self.won.is_some() {
+            return Err(error::GameError::GameOver);
+        }
+        let mut undo = Undo {
+            meeple: Player::ALL.map(|player| self.get_player_meeple(player)),
+            levels: [None; 2],
+            next_player: self.next_player,
+            eliminated: self.eliminated,
+            athena_moved_up: self.athena_moved_up,
+            won: self.won,
+            supply: self.supply,
+            hash: self.hash,
+        };
+        match self.play_turn(turn, &mut undo) {
+            Ok(()) => Ok(undo),
+            Err(error) => {
+                self.undo(undo);
+                Err(error)
+            }
+        }
+    }
+
+    /// Takes back the turn `apply` returned `undo` for.
+    pub fn undo(&mut self, undo: Undo) {
+        for (player, meeple) in Player::ALL.into_iter().zip(undo.meeple) {
+            *self.get_player_meeple_mut(player) = meeple;
+        }
+        for (position, level) in undo.levels.into_iter().rev().flatten() {
+            self.set_construction(position, level);
+        }
+        self.next_player = undo.next_player;
+        self.eliminated = undo.eliminated;
+        self.athena_moved_up = undo.athena_moved_up;
+        self.won = undo.won;
+        self.supply = undo.supply;
+        self.hash = undo.hash;
+    }
+
+    fn play_turn(&mut self, turn: &turn::Turn, undo: &mut Undo) -> error::Result<()> {
+        let player = self.next_player;
+        let god = self.get_player_god(player);
+        let no_move_up = self.no_move_up();
+        if god == Some(God::Athena) {
+            self.set_athena_moved_up(false);
+        }
+        match *turn {
             Turn::Setup(p1, p2) => {
-                if self.get_player_meeple(self.next_player).is_empty() {
-                    return self.place_worker(*p1, *p2);
-                } else {
-                    return Err(error::GameError::InvalidMove);
+                if !self.get_player_meeple(player).is_empty() {
+                    return Err(error::GameError::SetupAlreadyDone);
                 }
+                return self.place(p1, p2);
+            }
+            Turn::MoveBuild { start, end, build } => {
+                let moved_up = self.get_construction(end) > self.get_construction(start);
+                self.step_move(start, end, no_move_up)?;
+                self.undoable_build(end, build, undo)?;
+                if god == Some(God::Athena) {
+                    self.set_athena_moved_up(moved_up);
+                }
+            }
+            Turn::FinalMove { start, end } => {
+                let Some(reason) = self.win_reason(start, end) else {
+                    return Err(error::GameError::NotAWinningMove(start, end));
+                };
+                self.step_move(start, end, no_move_up)?;
+                self.won = Some(self.victory(player, reason));
+            }
+            Turn::DoubleMove {
+                start,
+                middle,
+                end,
+                build,
+            } => {
+                if god != Some(God::Artemis) {
+                    return Err(error::GameError::MissingPower(God::Artemis));
+                }
+                if end == start {
+                    return Err(error::GameError::MoveBackToStart(start));
+                }
+                self.step_move(start, middle, no_move_up)?;
+                match build {
+                    Some(build) => {
+                        self.step_move(middle, end, no_move_up)?;
+                        self.undoable_build(end, build, undo)?;
+                    }
+                    None => {
+                        let Some(reason) = self.win_reason(middle, end) else {
+                            return Err(error::GameError::NotAWinningMove(middle, end));
+                        };
+                        self.step_move(middle, end, no_move_up)?;
+                        self.won = Some(self.victory(player, reason));
+                    }
+                }
+            }
+            Turn::DoubleBuild {
+                start,
+                end,
+                build,
+                second_build,
+            } => {
+                match god {
+                    Some(God::Demeter) if second_build != build => {}
+                    Some(God::Hephaestus) if second_build == build => {}
+                    Some(God::Demeter | God::Hephaestus) => {
+                        return Err(error::GameError::InvalidSecondBuild(second_build));
+                    }
+                    // Building twice on the same tile is Hephaestus' power.
+                    _ if second_build == build => {
+                        return Err(error::GameError::MissingPower(God::Hephaestus));
+                    }
+                    _ => return Err(error::GameError::MissingPower(God::Demeter)),
+                }
+                self.step_move(start, end, no_move_up)?;
+                self.undoable_build(end, build, undo)?;
+                if god == Some(God::Hephaestus)
+                    && self.get_construction(build) == Construction::ThirdLevel
+                {
+                    return Err(error::GameError::InvalidSecondBuild(second_build));
+                }
+                self.undoable_build(end, second_build, undo)?;
+            }
+            Turn::DomeBuild { start, end, build } => {
+                if god != Some(God::Atlas) {
+                    return Err(error::GameError::MissingPower(God::Atlas));
+                }
+                self.step_move(start, end, no_move_up)?;
+                self.check_build(end, build)?;
+                undo.save_level(build, self.get_construction(build));
+                self.build_dome(build)?;
+            }
+            Turn::BuildMoveBuild {
+                first_build,
+                start,
+                end,
+                build,
+            } => {
+                if god != Some(God::Prometheus) {
+                    return Err(error::GameError::MissingPower(God::Prometheus));
+                }
+                self.undoable_build(start, first_build, undo)?;
+                self.step_move(start, end, true)?;
+                self.undoable_build(end, build, undo)?;
+            }
+        }
+        self.switch_player();
+        self.eliminate_blocked();
+        Ok(())
+    }
+
+    // While more than two players remain, the player to move is eliminated if
+    // they cannot move. Between the last two, `outcome` reports the block.
+    fn eliminate_blocked(&mut self) {
+        while self.won.is_none()
+            && self.remaining_players().count() > 2
+            && !self.get_player_meeple(self.next_player).is_empty()
+            && self.possible_move().is_empty()
+        {
+            self.eliminate(WinReason::Blocked);
+        }
+    }
+
+    // Removes the player to move and their workers, the last side standing
+    // winning for `reason`.
+    fn eliminate(&mut self, reason: WinReason) {
+        let player = self.next_player;
+        for position in self.get_player_meeple(player) {
+            self.hash ^= zobrist::worker(position, player);
+        }
+        *self.get_player_meeple_mut(player) = PositionSet::new();
+        self.eliminated[player as usize] = true;
+        self.switch_player();
+        let next = self.next_player;
+        if self
+            .remaining_players()
+            .all(|player| self.same_side(player, next))
+        {
+            self.won = Some(self.victory(next, reason));
+        }
+    }
+
+    /// The board after the player to move resigned.
+    pub fn resign(&self) -> Self {
+        self.forfeit(WinReason::Resignation)
+    }
+
+    /// The board after the player to move lost for `reason`, such as running
+    /// out of time. With more than two players left they are eliminated,
+    /// otherwise their opponent wins.
+    pub fn forfeit(&self, reason: WinReason) -> Self {
+        let mut board = self.clone();
+        if board.won.is_none() {
+            if board.remaining_players().count() > 2 {
+                board.eliminate(reason);
+                board.eliminate_blocked();
+            } else {
+                let winner = board.player_after(board.next_player);
+                board.won = Some(board.victory(winner, reason));
             }
-            Turn::MoveBuild { start, end, build } => (*start, *end, Some(*build)),
-            Turn::FinalMove { start, end } => (*start, *end, None),
-        };
-        if !Position::are_neighbors(start, end) {
-            return Err(error::GameError::InvalidMove);
         }
+        board
+    }
 
+    /// Like `forfeit`, for `player` who may not be the one to move, such as a
+    /// network opponent leaving. Out of turn, only one of the last two players
+    /// can forfeit.
+    pub fn forfeit_by(&self, player: Player, reason: WinReason) -> Self {
+        if player == self.next_player {
+            return self.forfeit(reason);
+        }
+        let mut board = self.clone();
+        if board.won.is_none() && board.remaining_players().count() == 2 {
+            let winner = board.player_after(player);
+            board.won = Some(board.victory(winner, reason));
+        }
+        board
+    }
+
+    /// Whether moving from `start` to `end` wins the game for the player to move.
+    pub fn is_winning_move(&self, start: Position, end: Position) -> bool {
+        self.win_reason(start, end).is_some()
+    }
+
+    fn win_reason(&self, start: Position, end: Position) -> Option<WinReason> {
+        let from = self.get_construction(start);
+        let to = self.get_construction(end);
+        if to == Construction::ThirdLevel {
+            Some(WinReason::ThirdFloor)
+        } else if self.get_player_god(self.next_player) == Some(God::Pan)
+            && from as u8 >= to as u8 + 2
+        {
+            Some(WinReason::Power(God::Pan))
+        } else {
+            None
+        }
+    }
+
+    /// How the game ended, if it did: either the previous turn won it, or the
+    /// player to move has no legal turn left.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.won.is_some() {
+            return self.won;
+        }
+        let blocked =
+            !self.get_player_meeple(self.next_player).is_empty() && self.possible_move().is_empty();
+        blocked.then(|| self.victory(self.previous_player(), WinReason::Blocked))
+    }
+
+    /// Checks that the worker on `start` may move to `end` this turn.
+    pub fn check_worker_move(&self, start: Position, end: Position) -> error::Result<()> {
+        self.check_move(start, end, self.no_move_up())
+    }
+
+    /// Checks that the worker on `start` may build on `build` after moving to `end`.
+    pub fn check_worker_build(
+        &self,
+        start: Position,
+        end: Position,
+        build: Position,
+    ) -> error::Result<()> {
+        let mut moved = self.clone();
+        moved.move_worker(start, end);
+        moved.check_build(end, build)?;
+        moved.build(build)
+    }
+
+    fn check_move(&self, start: Position, end: Position, no_move_up: bool) -> error::Result<()> {
         if !self.get_player_meeple(self.next_player).contains(start) {
-            return Err(error::GameError::InvalidMove);
+            return Err(error::GameError::NotYourWorker(start));
+        }
+        if !Position::are_neighbors(start, end) {
+            return Err(error::GameError::NotAdjacent(start, end));
+        }
+        if !self.size.positions().contains(end) {
+            return Err(error::GameError::OffBoard(end));
+        }
+        if self.domes().contains(end) {
+            return Err(error::GameError::MoveOnDome(end));
+        }
+        let from = self.get_construction(start);
+        let to = self.get_construction(end);
+        if !from.can_move(to) {
+            return Err(error::GameError::ClimbTooHigh(start, end));
+        }
+        if no_move_up && to > from {
+            return Err(error::GameError::MoveUpForbidden(start, end));
         }
+        if self.get_opponents_meeple(self.next_player).contains(end) {
+            let displaced = match self.get_player_god(self.next_player) {
+                Some(God::Apollo) => true,
+                Some(God::Minotaur) => Position::beyond(start, end).is_some_and(|behind| {
+                    self.size.positions().contains(behind)
+                        && !self.get_meeple().contains(behind)
+                        && !self.domes().contains(behind)
+                }),
+                _ => false,
+            };
+            if !displaced {
+                return Err(error::GameError::DestinationOccupied(end));
+            }
+        } else if self.get_meeple().contains(end) {
+            return Err(error::GameError::DestinationOccupied(end));
+        }
+        Ok(())
+    }
 
-        if self.get_meeple().contains(end) || self.dome.contains(end) {
-            return Err(error::GameError::InvalidMove);
+    fn check_build(&self, worker: Position, position: Position) -> error::Result<()> {
+        if !Position::are_neighbors(worker, position) {
+            return Err(error::GameError::NotAdjacent(worker, position));
         }
-        if !self.third_floor.contains(end) && build.is_none() {
-            return Err(error::GameError::InvalidMove);
+        if !self.size.positions().contains(position) {
+            return Err(error::GameError::OffBoard(position));
         }
+        if self.get_meeple().contains(position) {
+            return Err(error::GameError::BuildOnWorker(position));
+        }
+        if self.domes().contains(position) {
+            return Err(error::GameError::BuildOnDome(position));
+        }
+        Ok(())
+    }
 
-        let mut new_board = self.clone();
-        let player_meeple = new_board.get_player_meeple_mut(self.next_player);
+    fn has_piece(&self, piece: Construction) -> bool {
+        self.supply.is_none_or(|supply| supply.remaining(piece) > 0)
+    }
+
+    // Takes `piece` from the supply, if it is limited.
+    fn take_piece(&mut self, piece: Construction) -> error::Result<()> {
+        if !self.has_piece(piece) {
+            return Err(error::GameError::OutOfPieces(piece));
+        }
+        if let Some(supply) = &mut self.supply {
+            supply.take(piece);
+        }
+        Ok(())
+    }
+
+    fn step_move(&mut self, start: Position, end: Position, no_move_up: bool) -> error::Result<()> {
+        self.check_move(start, end, no_move_up)?;
+        self.move_worker(start, end);
+        Ok(())
+    }
+
+    fn step_build(&mut self, worker: Position, position: Position) -> error::Result<()> {
+        self.check_build(worker, position)?;
+        self.build(position)
+    }
+
+    // Like `step_build`, remembering the level of `position` in `undo`.
+    fn undoable_build(
+        &mut self,
+        worker: Position,
+        position: Position,
+        undo: &mut Undo,
+    ) -> error::Result<()> {
+        undo.save_level(position, self.get_construction(position));
+        self.step_build(worker, position)
+    }
+
+    /// Sets `position` to `level`, leaving the hash to the caller like changes
+    /// to the fields.
+    pub fn set_construction(&mut self, position: Position, level: Construction) {
+        for (height, plane) in self.levels.iter_mut().enumerate() {
+            if height < level as usize {
+                plane.add(position);
+            } else {
+                plane.remove(position);
+            }
+        }
+    }
+
+    // Moves a worker of the player to move, displacing an opponent as its god allows.
+    fn move_worker(&mut self, start: Position, end: Position) {
+        if let Some(opponent) = self.players().find(|&player| {
+            player != self.next_player && self.get_player_meeple(player).contains(end)
+        }) {
+            let displaced = match self.get_player_god(self.next_player) {
+                Some(God::Apollo) => start,
+                _ => Position::beyond(start, end).unwrap(),
+            };
+            let opponent_meeple = self.get_player_meeple_mut(opponent);
+            opponent_meeple.remove(end);
+            opponent_meeple.add(displaced);
+            self.hash ^= zobrist::worker(end, opponent) ^ zobrist::worker(displaced, opponent);
+        }
+        let player_meeple = self.get_player_meeple_mut(self.next_player);
         player_meeple.remove(start);
         player_meeple.add(end);
+        self.hash ^=
+            zobrist::worker(start, self.next_player) ^ zobrist::worker(end, self.next_player);
+    }
 
-        if let Some(construction_position) = build {
-            if !Position::are_neighbors(end, construction_position) {
-                return Err(error::GameError::InvalidMove);
-            }
-            if new_board.get_meeple().contains(construction_position) {
-                return Err(error::GameError::InvalidMove);
-            }
-            new_board.build(construction_position)?;
+    fn move_targets(&self, start: Position, no_move_up: bool) -> PositionSet {
+        let targets = start
+            .get_neighbors()
+            .intersection(self.climbable(self.get_construction(start), no_move_up));
+        // Occupied tiles are only reachable by displacing an opponent.
+        let displaced: PositionSet = targets
+            .intersection(self.get_opponents_meeple(self.next_player))
+            .into_iter()
+            .filter(|&end| self.check_move(start, end, no_move_up).is_ok())
+            .collect();
+        targets.difference(self.get_meeple()).union(displaced)
+    }
+
+    fn build_targets(&self, worker: Position) -> PositionSet {
+        let targets = worker
+            .get_neighbors()
+            .intersection(self.size.positions())
+            .difference(self.domes())
+            .difference(self.get_meeple());
+        if self.supply.is_none() {
+            return targets;
         }
-        new_board.next_player = self.next_player.other_player();
-        Ok(new_board)
+        targets
+            .into_iter()
+            .filter(|&position| {
+                self.get_construction(position)
+                    .build()
+                    .is_some_and(|piece| self.has_piece(piece))
+            })
+            .collect()
     }
 
     fn build(&mut self, position: Position) -> error::Result<()> {
-        if self.dome.contains(position) {
-            return Err(error::GameError::InvalidMove);
-        } else if self.third_floor.contains(position) {
-            self.third_floor.remove(position);
-            self.dome.add(position);
-        } else if self.second_floor.contains(position) {
-            self.second_floor.remove(position);
-            self.third_floor.add(position);
-        } else if self.first_floor.contains(position) {
-            self.first_floor.remove(position);
-            self.second_floor.add(position);
-        } else {
-            self.first_floor.add(position);
-        }
+        let before = self.get_construction(position);
+        let Some(piece) = before.build() else {
+            return Err(error::GameError::BuildOnDome(position));
+        };
+        self.take_piece(piece)?;
+        // The tile reaches the plane above its level.
+        self.levels[before as usize].add(position);
+        self.hash ^= zobrist::level(position, before) ^ zobrist::level(position, piece);
+        Ok(())
+    }
+
+    fn build_dome(&mut self, position: Position) -> error::Result<()> {
+        self.take_piece(Construction::Dome)?;
+        self.hash ^= zobrist::level(position, self.get_construction(position))
+            ^ zobrist::level(position, Construction::Dome);
+        self.set_construction(position, Construction::Dome);
         Ok(())
     }
 
     pub fn get_tiles(&self) -> impl Iterator<Item = (Position, Tile)> {
-        ALL_POSITIONS
+        self.size
+            .positions()
             .into_iter()
             .map(|pos| (pos, self.get_tile(pos)))
     }
 
+    /// Turns of the player to move, decoded from `moves`.
     pub fn possible_move(&self) -> Vec<turn::Turn> {
-        let mut acc = vec![];
-        if !self.get_player_meeple(self.next_player).is_empty() {
-            for orig_pos in self.get_player_meeple(self.next_player) {
-                for possible_move in orig_pos.get_neighbors().difference(self.get_meeple()) {
-                    if !self
-                        .get_construction(orig_pos)
-                        .can_move(self.get_construction(possible_move))
-                    {
-                        continue;
-                    }
-                    if self.third_floor.contains(possible_move) {
-                        acc.push(turn::Turn::FinalMove {
-                            start: orig_pos,
-                            end: possible_move,
-                        });
-                    } else {
-                        for possible_build in possible_move
-                            .get_neighbors()
-                            .difference(self.dome)
-                            .difference(self.get_meeple())
-                            .union([orig_pos].into())
-                        {
-                            acc.push(turn::Turn::MoveBuild {
-                                start: orig_pos,
-                                end: possible_move,
-                                build: possible_build,
-                            });
+        self.moves().map(|mv| self.turn(mv)).collect()
+    }
+
+    /// Moves of the player to move, generated lazily one worker step at a time.
+    pub fn moves(&self) -> Moves<'_> {
+        Moves::new(self)
+    }
+
+    /// The turn `mv` stands for, `mv` being a move of this board.
+    pub fn turn(&self, mv: Move) -> turn::Turn {
+        mv.decode(self.get_player_meeple(self.next_player))
+            .expect("move of another board")
+    }
+
+    /// Packs `turn` of the player to move.
+    pub fn encode(&self, turn: &turn::Turn) -> Move {
+        Move::encode(turn, self.get_player_meeple(self.next_player))
+    }
+
+    /// Whether the player to move has a winning turn, without generating the
+    /// others.
+    pub fn has_winning_turn(&self) -> bool {
+        if self.won.is_some() {
+            return false;
+        }
+        let no_move_up = self.no_move_up();
+        let artemis = self.get_player_god(self.next_player) == Some(God::Artemis);
+        self.get_player_meeple(self.next_player)
+            .into_iter()
+            .any(|start| {
+                self.move_targets(start, no_move_up).into_iter().any(|end| {
+                    self.is_winning_move(start, end)
+                        || artemis && {
+                            let mut moved = self.clone();
+                            moved.move_worker(start, end);
+                            moved
+                                .move_targets(end, no_move_up)
+                                .into_iter()
+                                .any(|second| second != start && moved.is_winning_move(end, second))
                         }
+                })
+            })
+    }
+
+    pub(super) fn empty_positions(&self) -> PositionSet {
+        self.size.positions().difference(self.get_meeple())
+    }
+
+    // Setups placing the first worker on `first`.
+    // Setups with a worker on `first` and one on a higher position, keeping the
+    // smallest of the pairs the `symmetries` of the board map to each other.
+    pub(super) fn push_setups(&self, first: Position, symmetries: &[Symmetry], acc: &mut Encoder) {
+        for second in self.empty_positions() {
+            let placed = PositionSet::from([first, second]);
+            if second > first
+                && symmetries
+                    .iter()
+                    .all(|&symmetry| placed <= placed.transform(symmetry, self.size))
+            {
+                acc.push(turn::Turn::Setup(first, second));
+            }
+        }
+    }
+
+    // Prometheus' first builds and the moves of the worker on `start`.
+    pub(super) fn worker_steps(&self, start: Position) -> (PositionSet, PositionSet) {
+        let first_builds = match self.get_player_god(self.next_player) {
+            Some(God::Prometheus) => self.build_targets(start),
+            _ => PositionSet::new(),
+        };
+        (first_builds, self.move_targets(start, self.no_move_up()))
+    }
+
+    // Turns moving the worker on `start` to `end` first.
+    pub(super) fn push_step(&self, start: Position, end: Position, acc: &mut Encoder) {
+        if self.is_winning_move(start, end) {
+            acc.push(turn::Turn::FinalMove { start, end });
+            return;
+        }
+        let god = self.get_player_god(self.next_player);
+        let mut moved = self.clone();
+        moved.move_worker(start, end);
+        for build in moved.build_targets(end) {
+            acc.push(turn::Turn::MoveBuild { start, end, build });
+            moved.push_power_builds(start, end, build, acc);
+        }
+        if god == Some(God::Artemis) {
+            moved.push_second_moves(start, end, acc);
+        }
+        if god == Some(God::Atlas) {
+            moved.push_dome_builds(start, end, acc);
+        }
+    }
+
+    // Turns building a second time, on a board where the worker already moved.
+    fn push_power_builds(
+        &self,
+        start: Position,
+        end: Position,
+        build: Position,
+        acc: &mut Encoder,
+    ) {
+        match self.get_player_god(self.next_player) {
+            Some(God::Demeter) => {
+                let mut built = self.clone();
+                built.build(build).unwrap();
+                for second_build in built.build_targets(end) {
+                    if second_build > build {
+                        acc.push(turn::Turn::DoubleBuild {
+                            start,
+                            end,
+                            build,
+                            second_build,
+                        });
                     }
                 }
             }
-        } else {
-            let empty_spot =
-                ALL_POSITIONS.difference(self.get_player_meeple(self.next_player.other_player()));
-            for pos1 in empty_spot {
-                for pos2 in empty_spot {
-                    acc.push(turn::Turn::Setup(pos1, pos2));
-                }
+            Some(God::Hephaestus)
+                if self.get_construction(build) <= Construction::FirstLevel
+                    && self
+                        .get_construction(build)
+                        .build()
+                        .and_then(Construction::build)
+                        .is_some_and(|piece| self.has_piece(piece)) =>
+            {
+                acc.push(turn::Turn::DoubleBuild {
+                    start,
+                    end,
+                    build,
+                    second_build: build,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Atlas' domes, on a board where the worker already moved from `start` to `end`. Domes
+    // on the third level are regular builds.
+    fn push_dome_builds(&self, start: Position, end: Position, acc: &mut Encoder) {
+        if !self.has_piece(Construction::Dome) {
+            return;
+        }
+        let targets = end
+            .get_neighbors()
+            .intersection(self.size.positions())
+            .difference(self.at_least(Construction::ThirdLevel))
+            .difference(self.get_meeple());
+        for build in targets {
+            acc.push(turn::Turn::DomeBuild { start, end, build });
+        }
+    }
+
+    // Artemis' second moves, on a board where the worker already moved from `start` to `middle`.
+    fn push_second_moves(&self, start: Position, middle: Position, acc: &mut Encoder) {
+        for end in self.move_targets(middle, self.no_move_up()) {
+            if end == start {
+                continue;
+            }
+            if self.is_winning_move(middle, end) {
+                acc.push(turn::Turn::DoubleMove {
+                    start,
+                    middle,
+                    end,
+                    build: None,
+                });
+                continue;
+            }
+            let mut moved = self.clone();
+            moved.move_worker(middle, end);
+            for build in moved.build_targets(end) {
+                acc.push(turn::Turn::DoubleMove {
+                    start,
+                    middle,
+                    end,
+                    build: Some(build),
+                });
+            }
+        }
+    }
+
+    // Prometheus' turns building on `first_build` before moving.
+    pub(super) fn push_build_first(
+        &self,
+        start: Position,
+        first_build: Position,
+        acc: &mut Encoder,
+    ) {
+        let mut built = self.clone();
+        built.build(first_build).unwrap();
+        for end in built.move_targets(start, true) {
+            let mut moved = built.clone();
+            moved.move_worker(start, end);
+            for build in moved.build_targets(end) {
+                acc.push(turn::Turn::BuildMoveBuild {
+                    first_build,
+                    start,
+                    end,
+                    build,
+                });
             }
         }
-        acc
     }
 
     pub fn setup_done(&self) -> bool {
-        self.get_meeple().len() == 4
+        self.players().all(|player| {
+            self.eliminated[player as usize] || !self.get_player_meeple(player).is_empty()
+        })
     }
 
     pub fn current_player(&self) -> Player {
         self.next_player
     }
+
+    /// One-line description of the board, such as `00000/001a00/00200b/0a040b0/00000 a`.
+    ///
+    /// Rows are listed from `a1`, each tile being its level (`4` for a dome)
+    /// followed by `a` to `d` for a worker of the first to fourth player. Then
+    /// comes the player to move and, when needed, the gods of every player
+    /// (`Pan/-`, which also gives the number of players), `+` if Athena moved
+    /// up during her last turn and the pieces left when they are limited
+    /// (`22/18/14/18`).
+    pub fn to_fen(&self) -> String {
+        let rows = (0..self.size.rows)
+            .map(|row| {
+                (0..self.size.cols)
+                    .map(|col| {
+                        let tile = self.get_tile(Position::new(row, col));
+                        let worker = match tile.player {
+                            Some(player) => player_to_fen(player),
+                            None => "",
+                        };
+                        format!("{}{worker}", tile.construction as u8)
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut fen = format!("{rows} {}", player_to_fen(self.next_player));
+        if self
+            .players()
+            .any(|player| self.get_player_god(player).is_some())
+            || self.player_count != 2
+            || self.athena_moved_up
+            || self.supply.is_some()
+        {
+            let gods: Vec<_> = self
+                .players()
+                .map(|player| {
+                    self.get_player_god(player)
+                        .map_or("-".to_string(), |god| god.to_string())
+                })
+                .collect();
+            fen += &format!(" {}", gods.join("/"));
+        }
+        if self.athena_moved_up {
+            fen += " +";
+        } else if self.supply.is_some() {
+            fen += " -";
+        }
+        if let Some(Supply(pieces)) = self.supply {
+            let pieces: Vec<_> = pieces.iter().map(u8::to_string).collect();
+            fen += &format!(" {}", pieces.join("/"));
+        }
+        fen
+    }
+
+    pub fn from_fen(fen: &str) -> std::result::Result<Self, error::FenError> {
+        let fields: Vec<_> = fen.split_whitespace().collect();
+        let (rows, player, gods, athena, supply) = match fields.as_slice() {
+            [rows, player] => (*rows, *player, "-/-", "-", None),
+            [rows, player, gods] => (*rows, *player, *gods, "-", None),
+            [rows, player, gods, athena] => (*rows, *player, *gods, *athena, None),
+            [rows, player, gods, athena, supply] => (*rows, *player, *gods, *athena, Some(*supply)),
+            _ => return Err(error::FenError::FieldCount(fields.len())),
+        };
+        let gods = gods
+            .split('/')
+            .map(|god| match god {
+                "-" => Ok(None),
+                god => god.parse().map(Some),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if !(2..=4).contains(&gods.len()) {
+            return Err(error::FenError::InvalidGods(fields[2].to_string()));
+        }
+        let mut board = Board::with_players(&gods);
+
+        let rows: Vec<_> = rows.split('/').collect();
+        if !(3..=MAX_ROWS).contains(&rows.len()) {
+            return Err(error::FenError::RowCount(rows.len()));
+        }
+        board.size.rows = rows.len();
+        for (row, tiles) in rows.into_iter().enumerate() {
+            let mut col = 0;
+            let mut chars = tiles.chars().peekable();
+            while let Some(level) = chars.next() {
+                let worker = chars.next_if(|c| c.is_ascii_alphabetic());
+                let invalid = || {
+                    let worker = worker.map(String::from).unwrap_or_default();
+                    error::FenError::InvalidTile(format!("{level}{worker}"))
+                };
+                if col >= MAX_COLS {
+                    col += 1;
+                    continue;
+                }
+                let position = Position::new(row, col);
+                let construction = match level {
+                    '0' => Construction::GroundLevel,
+                    '1' => Construction::FirstLevel,
+                    '2' => Construction::SecondLevel,
+                    '3' => Construction::ThirdLevel,
+                    '4' => Construction::Dome,
+                    _ => return Err(invalid()),
+                };
+                board.set_construction(position, construction);
+                if let Some(worker) = worker {
+                    if level == '4' {
+                        return Err(error::FenError::WorkerOnDome(position));
+                    }
+                    let player = player_from_fen(&worker.to_string())
+                        .filter(|&player| (player as usize) < board.player_count)
+                        .ok_or_else(invalid)?;
+                    board.get_player_meeple_mut(player).add(position);
+                }
+                col += 1;
+            }
+            if row == 0 {
+                board.size.cols = col;
+            }
+            if col != board.size.cols || !(3..=MAX_COLS).contains(&col) {
+                return Err(error::FenError::RowLength(row, col));
+            }
+        }
+
+        board.next_player = player_from_fen(player)
+            .filter(|&player| (player as usize) < board.player_count)
+            .ok_or_else(|| error::FenError::InvalidPlayer(player.to_string()))?;
+        board.athena_moved_up = match athena {
+            "-" => false,
+            "+" => true,
+            _ => return Err(error::FenError::InvalidAthenaFlag(athena.to_string())),
+        };
+        if let Some(supply) = supply {
+            let invalid = || error::FenError::InvalidSupply(supply.to_string());
+            let pieces: Vec<u8> = supply
+                .split('/')
+                .map(|count| count.parse().map_err(|_| invalid()))
+                .collect::<std::result::Result<_, _>>()?;
+            board.supply = Some(Supply(pieces.try_into().map_err(|_| invalid())?));
+        }
+
+        for player in board.players() {
+            if !matches!(board.get_player_meeple(player).len(), 0 | 2) {
+                return Err(error::FenError::WorkerCount(player));
+            }
+        }
+        // Workers are placed in turn order. Once the player to move placed
+        // theirs, players without workers were eliminated.
+        let placed: Vec<bool> = board
+            .players()
+            .map(|player| !board.get_player_meeple(player).is_empty())
+            .collect();
+        let next = board.next_player as usize;
+        if placed[next] {
+            for player in board.players().filter(|&player| !placed[player as usize]) {
+                board.eliminated[player as usize] = true;
+            }
+            if board.eliminated.contains(&true)
+                && (board.player_count == 2 || board.remaining_players().count() < 2)
+            {
+                return Err(error::FenError::SetupOrder);
+            }
+        } else if placed[..next].contains(&false) || placed[next..].contains(&true) {
+            return Err(error::FenError::SetupOrder);
+        }
+        board.rehash();
+        Ok(board)
+    }
+}
+
+fn player_to_fen(player: Player) -> &'static str {
+    match player {
+        Player::Player1 => "a",
+        Player::Player2 => "b",
+        Player::Player3 => "c",
+        Player::Player4 => "d",
+    }
+}
+
+fn player_from_fen(player: &str) -> Option<Player> {
+    match player {
+        "a" => Some(Player::Player1),
+        "b" => Some(Player::Player2),
+        "c" => Some(Player::Player3),
+        "d" => Some(Player::Player4),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -237,10 +1249,102 @@ mod test {
 
         assert_eq!(
             board.place_worker(p1, p2).err(),
-            Some(error::GameError::InvalidMove)
+            Some(error::GameError::DestinationOccupied(p1))
+        );
+        assert_eq!(
+            board.place_worker(p2, p2).err(),
+            Some(error::GameError::WorkersOverlap(p2))
+        );
+        let board = board
+            .place_worker(Position::new(0, 0), Position::new(0, 1))
+            .unwrap();
+        assert_eq!(
+            board
+                .action(&turn::Turn::Setup(Position::new(4, 4), Position::new(4, 3)))
+                .err(),
+            Some(error::GameError::SetupAlreadyDone)
         );
     }
 
+    #[test]
+    fn action_errors() {
+        let mut board = Board::new();
+        board.player1_meeple = [Position::new(1, 2), Position::new(4, 4)].into();
+        board.player2_meeple = [Position::new(1, 3), Position::new(0, 0)].into();
+        board.set_construction(Position::new(2, 2), Construction::SecondLevel);
+        board.set_construction(Position::new(0, 2), Construction::Dome);
+        let move_build = |start, end, build| turn::Turn::MoveBuild { start, end, build };
+
+        let cases = [
+            (
+                move_build(
+                    Position::new(1, 3),
+                    Position::new(2, 3),
+                    Position::new(2, 4),
+                ),
+                error::GameError::NotYourWorker(Position::new(1, 3)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(3, 2),
+                    Position::new(3, 3),
+                ),
+                error::GameError::NotAdjacent(Position::new(1, 2), Position::new(3, 2)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(1, 3),
+                    Position::new(2, 3),
+                ),
+                error::GameError::DestinationOccupied(Position::new(1, 3)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(0, 2),
+                    Position::new(0, 1),
+                ),
+                error::GameError::MoveOnDome(Position::new(0, 2)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(2, 2),
+                    Position::new(2, 1),
+                ),
+                error::GameError::ClimbTooHigh(Position::new(1, 2), Position::new(2, 2)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(1, 1),
+                    Position::new(0, 2),
+                ),
+                error::GameError::BuildOnDome(Position::new(0, 2)),
+            ),
+            (
+                move_build(
+                    Position::new(1, 2),
+                    Position::new(0, 1),
+                    Position::new(0, 0),
+                ),
+                error::GameError::BuildOnWorker(Position::new(0, 0)),
+            ),
+            (
+                turn::Turn::FinalMove {
+                    start: Position::new(1, 2),
+                    end: Position::new(1, 1),
+                },
+                error::GameError::NotAWinningMove(Position::new(1, 2), Position::new(1, 1)),
+            ),
+        ];
+        for (turn, expected) in cases {
+            assert_eq!(board.action(&turn).err(), Some(expected));
+        }
+    }
+
     #[test]
     fn action() {
         let mut board = Board::new();
@@ -296,4 +1400,630 @@ mod test {
             assert!(Position::are_neighbors(occupied, neighbor));
         }
     }
+
+    fn god_board(god: God) -> Board {
+        let mut board = Board::with_gods(Some(god), None);
+        board.player1_meeple = [Position::new(2, 2), Position::new(4, 4)].into();
+        board.player2_meeple = [Position::new(2, 3), Position::new(0, 0)].into();
+        board
+    }
+
+    #[test]
+    fn apollo_swaps_workers() {
+        let board = god_board(God::Apollo)
+            .action(&turn::Turn::MoveBuild {
+                start: Position::new(2, 2),
+                end: Position::new(2, 3),
+                build: Position::new(2, 4),
+            })
+            .unwrap();
+        assert_eq!(
+            board.get_tile(Position::new(2, 3)).player,
+            Some(Player::Player1)
+        );
+        assert_eq!(
+            board.get_tile(Position::new(2, 2)).player,
+            Some(Player::Player2)
+        );
+    }
+
+    #[test]
+    fn minotaur_pushes_workers() {
+        let board = god_board(God::Minotaur);
+        let new_board = board
+            .action(&turn::Turn::MoveBuild {
+                start: Position::new(2, 2),
+                end: Position::new(2, 3),
+                build: Position::new(2, 2),
+            })
+            .unwrap();
+        assert_eq!(
+            new_board.get_tile(Position::new(2, 4)).player,
+            Some(Player::Player2)
+        );
+
+        let mut blocked = board.clone();
+        blocked.set_construction(Position::new(2, 4), Construction::Dome);
+        assert!(
+            blocked
+                .action(&turn::Turn::MoveBuild {
+                    start: Position::new(2, 2),
+                    end: Position::new(2, 3),
+                    build: Position::new(2, 2),
+                })
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn athena_prevents_moving_up() {
+        let mut board = god_board(God::Athena);
+        board.set_construction(Position::new(1, 1), Construction::FirstLevel);
+        board.set_construction(Position::new(0, 1), Construction::FirstLevel);
+        let board = board
+            .action(&turn::Turn::MoveBuild {
+                start: Position::new(2, 2),
+                end: Position::new(1, 1),
+                build: Position::new(1, 2),
+            })
+            .unwrap();
+        assert!(board.athena_moved_up);
+        assert!(
+            board
+                .possible_move()
+                .iter()
+                .all(|turn| !matches!(turn, turn::Turn::MoveBuild { end, .. }
+                    if *end == Position::new(0, 1) || *end == Position::new(1, 2)))
+        );
+    }
+
+    #[test]
+    fn pan_wins_by_moving_down() {
+        let mut board = god_board(God::Pan);
+        board.set_construction(Position::new(2, 2), Construction::SecondLevel);
+        let turn = turn::Turn::FinalMove {
+            start: Position::new(2, 2),
+            end: Position::new(1, 1),
+        };
+        assert_eq!(
+            board.action(&turn).unwrap().outcome(),
+            Some(Outcome {
+                winner: Player::Player1,
+                teammate: None,
+                reason: WinReason::Power(God::Pan),
+            })
+        );
+        assert!(
+            board
+                .possible_move()
+                .iter()
+                .filter(|turn| turn.is_final())
+                .count()
+                >= 7
+        );
+        board.player1_god = None;
+        assert!(board.action(&turn).is_err());
+    }
+
+    #[test]
+    fn outcome() {
+        let board = Board::from_fen("0a0000/02a300/00000/00000/0b0b000 a").unwrap();
+        assert_eq!(board.outcome(), None);
+        let won = board
+            .action(&turn::Turn::FinalMove {
+                start: Position::new(1, 1),
+                end: Position::new(1, 2),
+            })
+            .unwrap();
+        assert_eq!(
+            won.outcome(),
+            Some(Outcome {
+                winner: Player::Player1,
+                teammate: None,
+                reason: WinReason::ThirdFloor,
+            })
+        );
+        assert!(won.possible_move().is_empty());
+        assert_eq!(
+            won.action(&turn::Turn::Setup(Position::new(2, 2), Position::new(3, 3))),
+            Err(error::GameError::GameOver)
+        );
+
+        let blocked = Board::from_fen("0a0a444/44400/00000/00000/000b0b0 a").unwrap();
+        assert_eq!(
+            blocked.outcome(),
+            Some(Outcome {
+                winner: Player::Player2,
+                teammate: None,
+                reason: WinReason::Blocked,
+            })
+        );
+        assert_eq!(Board::new().outcome(), None);
+        assert_eq!(
+            Board::new().resign().outcome(),
+            Some(Outcome {
+                winner: Player::Player2,
+                teammate: None,
+                reason: WinReason::Resignation,
+            })
+        );
+        // Out of turn, as when a network opponent resigns.
+        assert_eq!(
+            Board::new()
+                .forfeit_by(Player::Player2, WinReason::Resignation)
+                .outcome()
+                .map(|outcome| outcome.winner),
+            Some(Player::Player1)
+        );
+    }
+
+    #[test]
+    fn more_players() {
+        let board = Board::with_players(&[None; 3]);
+        let board = [(0, 0), (1, 1), (2, 2)]
+            .into_iter()
+            .fold(board, |board, (row, col)| {
+                board
+                    .action(&turn::Turn::Setup(
+                        Position::new(row, col),
+                        Position::new(row, col + 1),
+                    ))
+                    .unwrap()
+            });
+        assert_eq!(board.current_player(), Player::Player1);
+        assert!(!board.get_player_meeple(Player::Player3).is_empty());
+
+        // The third player is blocked, so their workers leave the board.
+        let board = Board::from_fen("0c0c444/44400/0a0a000/00000/0b0b000 b -/-/-").unwrap();
+        let board = board
+            .action(&turn::Turn::MoveBuild {
+                start: Position::new(4, 0),
+                end: Position::new(3, 0),
+                build: Position::new(4, 0),
+            })
+            .unwrap();
+        assert_eq!(board.current_player(), Player::Player1);
+        assert!(board.get_player_meeple(Player::Player3).is_empty());
+        assert_eq!(board.outcome(), None);
+        assert_eq!(board.to_fen(), "00444/44400/0a0a000/0b0000/10b000 a -/-/-");
+        assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board);
+        assert_eq!(board.resign().outcome().unwrap().winner, Player::Player2);
+
+        // Teams of the first and third against the second and fourth players.
+        let board =
+            Board::from_fen("2c30c00/0a0a000/00000/0d0d000/0b0b000 c -/Apollo/-/-").unwrap();
+        let won = board
+            .action(&turn::Turn::FinalMove {
+                start: Position::new(0, 0),
+                end: Position::new(0, 1),
+            })
+            .unwrap();
+        assert_eq!(
+            won.outcome(),
+            Some(Outcome {
+                winner: Player::Player3,
+                teammate: Some(Player::Player1),
+                reason: WinReason::ThirdFloor,
+            })
+        );
+        assert_eq!(
+            won.outcome().unwrap().to_string(),
+            "Player 3 and Player 1 won by reaching the third floor"
+        );
+        let apollo =
+            Board::from_fen("0c0c000/00000/0a0a000/0b0d000/0b0d000 b -/Apollo/-/-").unwrap();
+        assert_eq!(
+            apollo.check_move(Position::new(3, 0), Position::new(3, 1), false),
+            Err(error::GameError::DestinationOccupied(Position::new(3, 1)))
+        );
+        assert!(
+            apollo
+                .check_move(Position::new(3, 0), Position::new(2, 0), false)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn power_turns() {
+        let count = |god: God, matches: fn(&turn::Turn) -> bool| {
+            god_board(god)
+                .possible_move()
+                .iter()
+                .filter(|turn| matches(turn))
+                .count()
+        };
+        assert!(
+            count(God::Artemis, |turn| matches!(
+                turn,
+                turn::Turn::DoubleMove { .. }
+            )) > 0
+        );
+        assert!(
+            count(God::Demeter, |turn| matches!(
+                turn,
+                turn::Turn::DoubleBuild { .. }
+            )) > 0
+        );
+        assert!(
+            count(God::Atlas, |turn| matches!(
+                turn,
+                turn::Turn::DomeBuild { .. }
+            )) > 0
+        );
+        assert!(
+            count(God::Prometheus, |turn| matches!(
+                turn,
+                turn::Turn::BuildMoveBuild { .. }
+            )) > 0
+        );
+        assert_eq!(
+            count(God::Hephaestus, |turn| matches!(
+                turn,
+                turn::Turn::DoubleBuild { .. }
+            )),
+            count(God::Hephaestus, |turn| matches!(
+                turn,
+                turn::Turn::MoveBuild { .. }
+            ))
+        );
+        assert!(god_board(God::Artemis).possible_move().iter().all(
+            |turn| !matches!(turn, turn::Turn::DoubleMove { start, end, .. } if start == end)
+        ));
+
+        // Without a god, the error names the one the double build needs.
+        let mut board = Board::new();
+        board.player1_meeple = [Position::new(1, 1), Position::new(3, 3)].into();
+        board.player2_meeple = [Position::new(1, 3), Position::new(3, 1)].into();
+        let double_build = |second_build| turn::Turn::DoubleBuild {
+            start: Position::new(1, 1),
+            end: Position::new(2, 1),
+            build: Position::new(2, 2),
+            second_build,
+        };
+        assert_eq!(
+            board.action(&double_build(Position::new(2, 2))).err(),
+            Some(error::GameError::MissingPower(God::Hephaestus))
+        );
+        assert_eq!(
+            board.action(&double_build(Position::new(2, 0))).err(),
+            Some(error::GameError::MissingPower(God::Demeter))
+        );
+    }
+
+    #[test]
+    fn possible_moves_with_gods_are_valid() {
+        use rand::prelude::*;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for god in crate::game::god::ALL_GODS {
+            for _ in 0..5 {
+                let mut board = Board::with_gods(Some(god), Some(God::Athena));
+                loop {
+                    let possible_moves = board.possible_move();
+                    for turn in &possible_moves {
+                        assert!(board.action(turn).is_ok(), "{god}: {turn:?}");
+                    }
+                    let Some(turn) = possible_moves.choose(&mut rng) else {
+                        break;
+                    };
+                    if turn.is_final() {
+                        break;
+                    }
+                    board = board.action(turn).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_undo_round_trips() {
+        use rand::prelude::*;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut boards = vec![];
+        for god in crate::game::god::ALL_GODS {
+            boards.push(Board::with_gods(Some(god), Some(God::Minotaur)));
+            boards.push(Board::with_players(&[Some(god), None, Some(God::Apollo)]));
+        }
+        boards.push(Board {
+            supply: Some(Supply([3, 2, 1, 1])),
+            ..Board::with_players(&[None; 4])
+        });
+        for mut board in boards {
+            loop {
+                let possible_moves = board.possible_move();
+                for turn in &possible_moves {
+                    let before = board.clone();
+                    let undo = board.apply(turn).unwrap();
+                    assert_eq!(board.hash, zobrist::hash(&board), "{turn}");
+                    board.undo(undo);
+                    assert_eq!(board, before, "{turn}");
+                }
+                let Some(turn) = possible_moves.choose(&mut rng) else {
+                    break;
+                };
+                // Fails after the worker moved, building under itself.
+                if let Turn::MoveBuild { start, end, .. } = *turn {
+                    let before = board.clone();
+                    let illegal = Turn::MoveBuild {
+                        start,
+                        end,
+                        build: end,
+                    };
+                    assert!(board.apply(&illegal).is_err());
+                    assert_eq!(board, before);
+                }
+                board.apply(turn).unwrap();
+                if board.won.is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn move_targets_match_check_move() {
+        use rand::prelude::*;
+        let check = |board: &Board| {
+            for start in board.get_player_meeple(board.next_player) {
+                for no_move_up in [false, true] {
+                    let expected: PositionSet = start
+                        .get_neighbors()
+                        .into_iter()
+                        .filter(|&end| board.check_move(start, end, no_move_up).is_ok())
+                        .collect();
+                    assert_eq!(board.move_targets(start, no_move_up), expected, "{start}");
+                }
+            }
+        };
+        // Pushed onto the third level, the worker on c3 cannot stay on it.
+        check(&Board::from_fen("02021b/34134/123a11a/20b313/02221 a Apollo/Minotaur").unwrap());
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for god in [God::Apollo, God::Minotaur, God::Athena, God::Atlas] {
+            let mut board = Board::with_gods(Some(god), Some(God::Hephaestus));
+            while board.won.is_none() {
+                check(&board);
+                let Some(turn) = board.possible_move().choose(&mut rng).cloned() else {
+                    break;
+                };
+                board.apply(&turn).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        let board = Board::from_fen("00000/00a000/00000/0000a0/00000 b").unwrap();
+        let mirrored = board.transform(Symmetry::FlipCols);
+        assert_ne!(mirrored, board);
+        let (canonical, symmetry) = board.canonical();
+        assert_eq!(mirrored.canonical().0, canonical);
+        assert_eq!(board.transform(symmetry), canonical);
+        assert_eq!(canonical.transform(symmetry.inverse()), board);
+        assert_eq!(canonical.hash, zobrist::hash(&canonical));
+        // The workers sit on a diagonal, either side of the center.
+        assert_eq!(
+            board.symmetries(),
+            [
+                Symmetry::Identity,
+                Symmetry::Rotate180,
+                Symmetry::Transpose,
+                Symmetry::AntiTranspose
+            ]
+        );
+    }
+
+    #[test]
+    fn setups_up_to_symmetry() {
+        use std::collections::HashSet;
+        for fen in [
+            "00000/00000/00000/00000/00000 a",
+            "00000/00a000/00000/000a00/00000 b",
+            "0000/0000/0100/0000 a",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let empty = board.empty_positions();
+            let all: HashSet<Board> = empty
+                .into_iter()
+                .flat_map(|first| empty.into_iter().map(move |second| (first, second)))
+                .filter(|(first, second)| first != second)
+                .map(|(first, second)| {
+                    let setup = Turn::Setup(first, second);
+                    board.action(&setup).unwrap().canonical().0
+                })
+                .collect();
+            let setups = board.possible_move();
+            let generated: HashSet<Board> = setups
+                .iter()
+                .map(|setup| board.action(setup).unwrap().canonical().0)
+                .collect();
+            assert_eq!(setups.len(), generated.len(), "{fen}");
+            assert_eq!(generated, all, "{fen}");
+        }
+    }
+
+    #[test]
+    fn fen() {
+        let board = Board::from_fen("00000/001a00/00200b/0a040b0/00000 a").unwrap();
+        assert_eq!(
+            board.get_tile(Position::new(1, 2)).player,
+            Some(Player::Player1)
+        );
+        assert_eq!(
+            board.get_tile(Position::new(2, 2)).construction,
+            Construction::SecondLevel
+        );
+        assert!(board.domes().contains(Position::new(3, 2)));
+        assert_eq!(board.get_player_meeple(Player::Player2).len(), 2);
+
+        for fen in [
+            "00000/00000/00000/00000/00000 a",
+            "00000/001a00/00200b/0a040b0/00000 b",
+            "3a0000/00000/00200b/0a040b0/00000 a Pan/-",
+            "0a0a000/00000/00000/00000/00000 b -/Athena +",
+            "0a0a0000/000000/000000/0b0b0000 a",
+            "00000/00000/00000/00000/00000 a -/- - 22/18/14/18",
+            "0a0a000/00000/00000/00000/00000 b Atlas/- + 0/1/2/3",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn board_size() {
+        let board = Board::from_fen("0000000/0a0000b00/0000000/000b0a000 a").unwrap();
+        assert_eq!(board.size, Size { rows: 4, cols: 7 });
+        assert_eq!(board.get_tiles().count(), 28);
+        assert!(board.possible_move().iter().all(
+            |turn| matches!(turn, turn::Turn::MoveBuild { end, build, .. }
+                    if board.size.positions().contains(*end)
+                        && board.size.positions().contains(*build))
+        ));
+        let off_board = turn::Turn::MoveBuild {
+            start: Position::new(3, 3),
+            end: Position::new(4, 3),
+            build: Position::new(3, 4),
+        };
+        assert_eq!(
+            board.action(&off_board),
+            Err(error::GameError::OffBoard(Position::new(4, 3)))
+        );
+
+        let minotaur = Board::from_fen("0a0000/00000/00000/000a00/000b0b0 a Minotaur/-").unwrap();
+        assert!(!minotaur.possible_move().iter().any(|turn| matches!(
+            turn,
+            turn::Turn::MoveBuild { end, .. } if *end == Position::new(4, 2)
+        )));
+    }
+
+    #[test]
+    fn limited_supply() {
+        let board =
+            Board::from_fen("0a1000/00000/000a00/00000/0b0b000 a -/- - 22/0/14/18").unwrap();
+        let targets: PositionSet = board
+            .possible_move()
+            .iter()
+            .filter_map(|turn| match turn {
+                turn::Turn::MoveBuild { build, .. } => Some(*build),
+                _ => None,
+            })
+            .collect();
+        assert!(!targets.contains(Position::new(0, 1)));
+        assert_eq!(
+            board.check_worker_build(
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(0, 1)
+            ),
+            Err(error::GameError::OutOfPieces(Construction::SecondLevel))
+        );
+        let built = board
+            .action(&turn::Turn::MoveBuild {
+                start: Position::new(0, 0),
+                end: Position::new(1, 0),
+                build: Position::new(2, 0),
+            })
+            .unwrap();
+        assert_eq!(built.supply, Some(Supply([21, 0, 14, 18])));
+
+        let atlas =
+            Board::from_fen("0a1000/00000/000a00/00000/0b0b000 a Atlas/- - 0/0/0/1").unwrap();
+        let turns = atlas.possible_move();
+        assert!(
+            turns
+                .iter()
+                .all(|turn| matches!(turn, turn::Turn::DomeBuild { .. }))
+        );
+        let domed = atlas.action(&turns[0]).unwrap();
+        assert!(domed.possible_move().is_empty());
+        for god in [
+            None,
+            Some(God::Apollo),
+            Some(God::Demeter),
+            Some(God::Hephaestus),
+        ] {
+            let mut board = Board {
+                supply: Some(Supply([3, 2, 1, 1])),
+                ..Board::with_gods(god, None)
+            };
+            while let Some(turn) = board.possible_move().first().cloned() {
+                board = board.action(&turn).unwrap();
+            }
+            assert_eq!(
+                board.supply.map(|supply| supply.0[..3].iter().sum::<u8>()),
+                Some(0)
+            );
+        }
+    }
+
+    #[test]
+    fn fen_errors() {
+        for (fen, expected) in [
+            ("00000/00000/00000/00000", error::FenError::FieldCount(1)),
+            ("00000/00000 a", error::FenError::RowCount(2)),
+            ("00/00/00/00 a", error::FenError::RowLength(0, 2)),
+            (
+                "00000/00000/00000/00000/00000 a -/- - 22/18/14",
+                error::FenError::InvalidSupply("22/18/14".to_string()),
+            ),
+            (
+                "00000/0000/00000/00000/00000 a",
+                error::FenError::RowLength(1, 4),
+            ),
+            (
+                "00000/000000/00000/00000/00000 a",
+                error::FenError::RowLength(1, 6),
+            ),
+            (
+                "00000/00500/00000/00000/00000 a",
+                error::FenError::InvalidTile("5".to_string()),
+            ),
+            (
+                "00000/004a0/00000/00000/00000 a",
+                error::FenError::WorkerOnDome(Position::new(1, 2)),
+            ),
+            (
+                "0a0a0a00/00000/00000/00000/00000 b",
+                error::FenError::WorkerCount(Player::Player1),
+            ),
+            (
+                "00000/0b0b000/00000/00000/00000 a",
+                error::FenError::SetupOrder,
+            ),
+            (
+                "00000/00000/00000/00000/00000 c",
+                error::FenError::InvalidPlayer("c".to_string()),
+            ),
+            (
+                "00000/00000/00000/00000/00000 a Zeus/-",
+                error::FenError::Notation(error::NotationError::UnknownGod("Zeus".to_string())),
+            ),
+        ] {
+            assert_eq!(Board::from_fen(fen).err(), Some(expected), "{fen}");
+        }
+    }
+
+    #[test]
+    fn zobrist_hash() {
+        use rand::prelude::*;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        for (god1, god2) in [
+            (None, None),
+            (Some(God::Apollo), Some(God::Minotaur)),
+            (Some(God::Atlas), Some(God::Athena)),
+        ] {
+            let mut board = Board::with_gods(god1, god2);
+            let mut seen = std::collections::HashSet::new();
+            while let Some(turn) = board.possible_move().choose(&mut rng).cloned() {
+                assert_eq!(board.hash, zobrist::hash(&board));
+                seen.insert(board.clone());
+                if turn.is_final() {
+                    break;
+                }
+                board = board.action(&turn).unwrap();
+            }
+            assert!(seen.len() > 4);
+        }
+        let board = Board::from_fen("00000/001a00/00200b/0a040b0/00000 a").unwrap();
+        assert_eq!(board.hash, zobrist::hash(&board));
+        assert_ne!(board.hash, Board::new().hash);
+    }
 }
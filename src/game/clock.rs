@@ -0,0 +1,195 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use super::{error::NotationError, prelude::Player};
+
+/// How much thinking time the players get.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// `base` for the whole game, plus `increment` after each turn.
+    Increment { base: Duration, increment: Duration },
+    /// The same time for every turn, unused time being lost.
+    PerMove(Duration),
+}
+
+// Notation: `5+3` for 5 minutes plus 3 seconds per turn, `10/move` for 10
+// seconds per turn.
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControl::Increment { base, increment } => {
+                write!(f, "{}+{}", base.as_secs() / 60, increment.as_secs())
+            }
+            TimeControl::PerMove(time) => write!(f, "{}/move", time.as_secs()),
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NotationError::InvalidTimeControl(s.to_string());
+        let seconds = |value: &str| {
+            value
+                .parse()
+                .map(Duration::from_secs)
+                .map_err(|_| invalid())
+        };
+        if let Some(time) = s.strip_suffix("/move") {
+            return match seconds(time)? {
+                Duration::ZERO => Err(invalid()),
+                time => Ok(TimeControl::PerMove(time)),
+            };
+        }
+        let (base, increment) = s.split_once('+').ok_or_else(invalid)?;
+        match (seconds(base)? * 60, seconds(increment)?) {
+            (Duration::ZERO, _) => Err(invalid()),
+            (base, increment) => Ok(TimeControl::Increment { base, increment }),
+        }
+    }
+}
+
+/// Remaining time of every player, running for one of them at a time.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    pub control: TimeControl,
+    remaining: [Duration; 4],
+    // The player whose time runs, and since when.
+    running: Option<(Player, Instant)>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let time = match control {
+            TimeControl::Increment { base, .. } => base,
+            TimeControl::PerMove(time) => time,
+        };
+        Self {
+            control,
+            remaining: [time; 4],
+            running: None,
+        }
+    }
+
+    /// Stops the running time, if any, and starts the time of `player`.
+    pub fn switch(&mut self, player: Player, now: Instant) {
+        self.stop(now);
+        if let TimeControl::PerMove(time) = self.control {
+            self.remaining[player as usize] = time;
+        }
+        self.running = Some((player, now));
+    }
+
+    /// Stops the running time, adding the increment to it.
+    pub fn stop(&mut self, now: Instant) {
+        let Some((player, _)) = self.running else {
+            return;
+        };
+        let remaining = self.remaining(player, now);
+        self.remaining[player as usize] = match self.control {
+            TimeControl::Increment { increment, .. } if !remaining.is_zero() => {
+                remaining + increment
+            }
+            _ => remaining,
+        };
+        self.running = None;
+    }
+
+    pub fn set_remaining(&mut self, player: Player, time: Duration) {
+        self.remaining[player as usize] = time;
+    }
+
+    pub fn remaining(&self, player: Player, now: Instant) -> Duration {
+        match self.running {
+            Some((running, since)) if running == player => {
+                self.remaining[player as usize].saturating_sub(now.saturating_duration_since(since))
+            }
+            _ => self.remaining[player as usize],
+        }
+    }
+
+    /// The player whose time runs, if it ran out.
+    pub fn flagged(&self, now: Instant) -> Option<Player> {
+        self.running
+            .map(|(player, _)| player)
+            .filter(|&player| self.remaining(player, now).is_zero())
+    }
+
+    /// Thinking time `player` can afford for their turn.
+    pub fn budget(&self, player: Player, now: Instant) -> Duration {
+        let remaining = self.remaining(player, now);
+        match self.control {
+            TimeControl::Increment { increment, .. } => {
+                (remaining / 20 + increment / 2).min(remaining / 2)
+            }
+            // Keeps a margin for the time spent outside the search.
+            TimeControl::PerMove(_) => remaining * 9 / 10,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn notation() {
+        for notation in ["5+3", "1+0", "10/move"] {
+            assert_eq!(
+                notation.parse::<TimeControl>().unwrap().to_string(),
+                notation
+            );
+        }
+        for notation in ["5", "0+3", "0/move", "a+b", "5+3+1"] {
+            assert_eq!(
+                notation.parse::<TimeControl>(),
+                Err(NotationError::InvalidTimeControl(notation.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn clock() {
+        let start = Instant::now();
+        let seconds = |n| start + Duration::from_secs(n);
+        let mut clock = Clock::new("1+2".parse().unwrap());
+        clock.switch(Player::Player1, start);
+        assert_eq!(
+            clock.remaining(Player::Player1, seconds(10)),
+            Duration::from_secs(50)
+        );
+        assert_eq!(
+            clock.budget(Player::Player1, seconds(10)),
+            Duration::from_millis(3500)
+        );
+        clock.switch(Player::Player2, seconds(10));
+        assert_eq!(
+            clock.remaining(Player::Player1, seconds(30)),
+            Duration::from_secs(52)
+        );
+        assert_eq!(clock.flagged(seconds(69)), None);
+        assert_eq!(clock.flagged(seconds(70)), Some(Player::Player2));
+        clock.stop(seconds(80));
+        assert_eq!(
+            clock.remaining(Player::Player2, seconds(90)),
+            Duration::ZERO
+        );
+
+        let mut clock = Clock::new("10/move".parse().unwrap());
+        clock.switch(Player::Player1, start);
+        clock.switch(Player::Player2, seconds(4));
+        clock.switch(Player::Player1, seconds(5));
+        assert_eq!(
+            clock.remaining(Player::Player1, seconds(5)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            clock.budget(Player::Player1, seconds(5)),
+            Duration::from_secs(9)
+        );
+    }
+}
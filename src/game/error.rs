@@ -1,9 +1,96 @@
 use thiserror::Error;
 
+use super::{
+    god::God,
+    prelude::{Construction, Player, Position},
+};
+
 #[derive(Error, Debug, PartialEq)]
 pub enum GameError {
-    #[error("invalid move")]
-    InvalidMove,
+    #[error("{0} is not one of your workers")]
+    NotYourWorker(Position),
+    #[error("{0} and {1} are not adjacent")]
+    NotAdjacent(Position, Position),
+    #[error("{0} is occupied")]
+    DestinationOccupied(Position),
+    #[error("cannot move onto the dome on {0}")]
+    MoveOnDome(Position),
+    #[error("cannot climb from {0} to {1}")]
+    ClimbTooHigh(Position, Position),
+    #[error("cannot move up from {0} to {1} this turn")]
+    MoveUpForbidden(Position, Position),
+    #[error("moving from {0} to {1} does not win")]
+    NotAWinningMove(Position, Position),
+    #[error("cannot move back to {0}")]
+    MoveBackToStart(Position),
+    #[error("{0} is off the board")]
+    OffBoard(Position),
+    #[error("no {0} pieces left")]
+    OutOfPieces(Construction),
+    #[error("the game is over")]
+    GameOver,
+    #[error("cannot build on the dome on {0}")]
+    BuildOnDome(Position),
+    #[error("cannot build on the worker on {0}")]
+    BuildOnWorker(Position),
+    #[error("cannot build a second time on {0}")]
+    InvalidSecondBuild(Position),
+    #[error("this turn requires the power of {0}")]
+    MissingPower(God),
+    #[error("no turn uses a god power from here")]
+    NoPowerTurn,
+    #[error("workers are already placed")]
+    SetupAlreadyDone,
+    #[error("both workers cannot be placed on {0}")]
+    WorkersOverlap(Position),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NotationError {
+    #[error("invalid position {0:?}")]
+    InvalidPosition(String),
+    #[error("invalid board size {0:?}")]
+    InvalidSize(String),
+    #[error("invalid time control {0:?}")]
+    InvalidTimeControl(String),
+    #[error("invalid turn {0:?}")]
+    InvalidTurn(String),
+    #[error("invalid header {0:?}")]
+    InvalidHeader(String),
+    #[error("unknown god {0:?}")]
+    UnknownGod(String),
+    #[error("unsupported save version {0:?}")]
+    UnsupportedVersion(String),
+    #[error("turn {0} is illegal: {1}")]
+    IllegalTurn(usize, GameError),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FenError {
+    #[error("expected 2 to 5 space separated fields, found {0}")]
+    FieldCount(usize),
+    #[error("expected 3 to 8 rows, found {0}")]
+    RowCount(usize),
+    #[error("expected 3 to 7 tiles on row {0}, as many as on the first row, found {1}")]
+    RowLength(usize, usize),
+    #[error("invalid tile {0:?}")]
+    InvalidTile(String),
+    #[error("invalid player {0:?}")]
+    InvalidPlayer(String),
+    #[error("invalid gods {0:?}")]
+    InvalidGods(String),
+    #[error("invalid Athena flag {0:?}")]
+    InvalidAthenaFlag(String),
+    #[error("invalid supply {0:?}")]
+    InvalidSupply(String),
+    #[error("worker on the dome on {0}")]
+    WorkerOnDome(Position),
+    #[error("{0} must have zero or two workers")]
+    WorkerCount(Player),
+    #[error("workers are not placed in turn order")]
+    SetupOrder,
+    #[error(transparent)]
+    Notation(#[from] NotationError),
 }
 
 pub type Result<T> = std::result::Result<T, GameError>;
@@ -0,0 +1,65 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::error::NotationError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum God {
+    /// May move into an opponent's space, swapping the two workers.
+    Apollo,
+    /// May move one additional time, but not back to the initial space.
+    Artemis,
+    /// After moving up, opponents cannot move up until her next turn.
+    Athena,
+    /// May build a dome at any level.
+    Atlas,
+    /// May build one additional time, but not on the same space.
+    Demeter,
+    /// May build one additional block (not a dome) on top of the first one.
+    Hephaestus,
+    /// May push an opponent's worker one space straight back.
+    Minotaur,
+    /// Also wins by moving down two or more levels.
+    Pan,
+    /// May build before moving, if the worker does not move up.
+    Prometheus,
+}
+
+pub const ALL_GODS: [God; 9] = [
+    God::Apollo,
+    God::Artemis,
+    God::Athena,
+    God::Atlas,
+    God::Demeter,
+    God::Hephaestus,
+    God::Minotaur,
+    God::Pan,
+    God::Prometheus,
+];
+
+impl God {
+    /// Whether the power makes turns of its own, rather than changing those of
+    /// the base rules.
+    pub fn has_power_turns(self) -> bool {
+        matches!(
+            self,
+            God::Artemis | God::Atlas | God::Demeter | God::Hephaestus | God::Prometheus
+        )
+    }
+}
+
+impl Display for God {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl FromStr for God {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_GODS
+            .into_iter()
+            .find(|god| god.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| NotationError::UnknownGod(s.to_string()))
+    }
+}
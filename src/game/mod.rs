@@ -1,8 +1,16 @@
 pub mod board;
+pub mod clock;
 pub mod error;
+pub mod god;
+pub mod movegen;
+pub mod perft;
 pub mod prelude;
+pub mod record;
+pub mod save;
 pub mod turn;
+pub mod zobrist;
 
+use god::God;
 use prelude::*;
 use turn::Turn;
 
@@ -13,25 +21,40 @@ pub struct Game {
     board: board::Board,
     current_turn: PartialTurn,
     selectable: PositionSet,
+    rejection: Option<error::GameError>,
+    // Boards before each played turn, most recent last.
+    history: Vec<(board::Board, Turn)>,
+    undone: Vec<(board::Board, Turn)>,
 }
 
 impl Game {
     pub fn new() -> Self {
-        Self {
-            board: board::Board::new(),
+        Self::from_board(board::Board::new())
+    }
+    pub fn from_board(board: board::Board) -> Self {
+        let mut game = Self {
+            board,
             current_turn: PartialTurn::NothingSetup,
-            selectable: ALL_POSITIONS,
-        }
+            selectable: PositionSet::new(),
+            rejection: None,
+            history: vec![],
+            undone: vec![],
+        };
+        game.start_turn();
+        game
     }
+
     fn reset_selectable(&mut self) {
+        self.rejection = None;
         let possible_moves = self.board.possible_move();
         self.selectable = match self.current_turn {
             PartialTurn::Nothing => possible_moves
                 .iter()
-                .map(|turn| match turn {
+                .filter_map(|turn| match turn {
                     Turn::Setup(_, _) => panic!("Not possible"),
-                    Turn::MoveBuild { start, .. } => *start,
-                    Turn::FinalMove { start, .. } => *start,
+                    Turn::MoveBuild { start, .. } => Some(*start),
+                    Turn::FinalMove { start, .. } => Some(*start),
+                    _ => None,
                 })
                 .collect(),
             PartialTurn::Selection(s) => possible_moves
@@ -43,24 +66,15 @@ impl Game {
                     _ => None,
                 })
                 .collect(),
-            PartialTurn::Move(s, e) => {
-                if matches!(
-                    self.board.get_tile(e).construction,
-                    Construction::ThirdLevel
-                ) {
-                    PositionSet::new()
-                } else {
-                    possible_moves
-                        .iter()
-                        .filter_map(|turn| match turn {
-                            Turn::MoveBuild { start, end, build } if s == *start && e == *end => {
-                                Some(*build)
-                            }
-                            _ => None,
-                        })
-                        .collect()
-                }
-            }
+            PartialTurn::Move(s, e) => possible_moves
+                .iter()
+                .filter_map(|turn| match turn {
+                    Turn::MoveBuild { start, end, build } if s == *start && e == *end => {
+                        Some(*build)
+                    }
+                    _ => None,
+                })
+                .collect(),
             PartialTurn::NothingSetup => self
                 .board
                 .get_tiles()
@@ -73,6 +87,12 @@ impl Game {
                 .filter(|&(position, tile)| position != first && tile.player.is_none())
                 .map(|(position, _)| position)
                 .collect(),
+            PartialTurn::Power(ref picked) => possible_moves
+                .iter()
+                .filter_map(Turn::power_selections)
+                .filter(|selections| selections.starts_with(picked))
+                .filter_map(|selections| selections.get(picked.len()).copied())
+                .collect(),
         };
     }
 
@@ -88,34 +108,99 @@ impl Game {
         self.reset_selectable();
     }
 
+    /// Switches between a turn of the base rules and one using the god power
+    /// of the player to move, keeping the worker picked.
+    pub fn toggle_power(&mut self) {
+        let turn = match self.current_turn {
+            PartialTurn::Nothing => PartialTurn::Power(vec![]),
+            PartialTurn::Selection(start) => PartialTurn::Power(vec![start]),
+            PartialTurn::Power(ref picked) => match picked[..] {
+                [start] => PartialTurn::Selection(start),
+                _ => PartialTurn::Nothing,
+            },
+            PartialTurn::Move(..) | PartialTurn::NothingSetup | PartialTurn::PartialSetup(_) => {
+                return;
+            }
+        };
+        let previous = std::mem::replace(&mut self.current_turn, turn);
+        self.reset_selectable();
+        if self.selectable.is_empty() {
+            self.current_turn = previous;
+            self.reset_selectable();
+            self.rejection = Some(error::GameError::NoPowerTurn);
+        }
+    }
+
+    /// Whether the current turn uses the god power of the player to move.
+    pub fn using_power(&self) -> bool {
+        matches!(self.current_turn, PartialTurn::Power(_))
+    }
+
+    /// Records why `position` cannot be selected to complete the current turn.
+    pub fn reject(&mut self, position: Position) {
+        self.rejection = match self.current_turn {
+            PartialTurn::Nothing => (!self
+                .board
+                .get_player_meeple(self.board.current_player())
+                .contains(position))
+            .then_some(error::GameError::NotYourWorker(position)),
+            PartialTurn::Selection(start) => self.board.check_worker_move(start, position).err(),
+            PartialTurn::Move(start, end) => {
+                self.board.check_worker_build(start, end, position).err()
+            }
+            PartialTurn::NothingSetup => self
+                .board
+                .get_tile(position)
+                .player
+                .map(|_| error::GameError::DestinationOccupied(position)),
+            PartialTurn::PartialSetup(first) => self.board.place_worker(first, position).err(),
+            PartialTurn::Power(ref picked) => (picked.is_empty()
+                && !self
+                    .board
+                    .get_player_meeple(self.board.current_player())
+                    .contains(position))
+            .then_some(error::GameError::NotYourWorker(position)),
+        };
+    }
+
     pub fn register_selection(&mut self, selection: Position) {
         match self.current_turn {
             PartialTurn::Nothing => {
                 self.current_turn = PartialTurn::Selection(selection);
             }
+            PartialTurn::Selection(start) if self.board.is_winning_move(start, selection) => {
+                return self.play(turn::Turn::FinalMove {
+                    start,
+                    end: selection,
+                });
+            }
             PartialTurn::Selection(start) => {
                 self.current_turn = PartialTurn::Move(start, selection);
             }
             PartialTurn::Move(start, end) => {
-                self.board = self
-                    .board
-                    .action(&turn::Turn::MoveBuild {
-                        start,
-                        end,
-                        build: selection,
-                    })
-                    .unwrap();
-                self.current_turn = PartialTurn::Nothing;
+                return self.play(turn::Turn::MoveBuild {
+                    start,
+                    end,
+                    build: selection,
+                });
             }
             PartialTurn::NothingSetup => {
                 self.current_turn = PartialTurn::PartialSetup(selection);
             }
             PartialTurn::PartialSetup(first) => {
-                self.board = self.board.place_worker(first, selection).unwrap();
-                self.current_turn = match self.board.current_player() {
-                    Player::Player1 => PartialTurn::Nothing,
-                    Player::Player2 => PartialTurn::NothingSetup,
-                };
+                return self.play(turn::Turn::Setup(first, selection));
+            }
+            PartialTurn::Power(ref mut picked) => {
+                picked.push(selection);
+                let picked = Some(picked.clone());
+                if let Some(turn) = self
+                    .board
+                    .possible_move()
+                    .into_iter()
+                    .find(|turn| turn.power_selections() == picked)
+                {
+                    return self.play(turn);
+                }
             }
         };
         self.reset_selectable();
@@ -128,43 +213,110 @@ impl Game {
             PartialTurn::Move(start, end) => [start, end].into(),
             PartialTurn::NothingSetup => [].into(),
             PartialTurn::PartialSetup(first) => [first].into(),
+            PartialTurn::Power(ref picked) => picked.iter().copied().collect(),
         }
     }
 
-    pub fn winner(&self) -> Option<Player> {
+    /// Positions picked so far in the current turn, in order.
+    pub fn pending_selections(&self) -> Vec<Position> {
         match self.current_turn {
-            PartialTurn::Move(_, end)
-                if matches!(
-                    self.board.get_tile(end).construction,
-                    Construction::ThirdLevel
-                ) =>
-            {
-                Some(self.board.current_player())
-            }
-            PartialTurn::Nothing if self.selectable().is_empty() => {
-                Some(self.board.current_player().other_player())
-            }
-            _ => None,
+            PartialTurn::Selection(start) => vec![start],
+            PartialTurn::Move(start, end) => vec![start, end],
+            PartialTurn::PartialSetup(first) => vec![first],
+            PartialTurn::Power(ref picked) => picked.clone(),
+            PartialTurn::Nothing | PartialTurn::NothingSetup => vec![],
         }
     }
 
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.board.outcome()
+    }
+
+    /// Ends the game, or eliminates the player to move in a game of more
+    /// players, in favour of their opponents.
+    pub fn forfeit(&mut self, reason: WinReason) {
+        self.board = self.board.forfeit(reason);
+        self.start_turn();
+    }
+
+    /// Like `forfeit`, for `player` who may not be the one to move.
+    pub fn forfeit_by(&mut self, player: Player, reason: WinReason) {
+        self.board = self.board.forfeit_by(player, reason);
+        self.start_turn();
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.outcome().map(|outcome| outcome.winner)
+    }
+
     pub fn next_action(&self) -> String {
+        let god = self.board.get_player_god(self.board.current_player());
         let action = match self.current_turn {
+            crate::game::turn::PartialTurn::Nothing if god.is_some_and(God::has_power_turns) => {
+                "Pick a worker! (p to use your power)"
+            }
             crate::game::turn::PartialTurn::Nothing => "Pick a worker!",
             crate::game::turn::PartialTurn::Selection(_) => "Move your worker!",
             crate::game::turn::PartialTurn::Move(_, _) => "Build!",
             crate::game::turn::PartialTurn::NothingSetup => "Place your first worker!",
             crate::game::turn::PartialTurn::PartialSetup(_) => "Place your second worker!",
+            crate::game::turn::PartialTurn::Power(ref picked) => power_action(god, picked.len()),
         };
-        format!("{}: {action}", self.board.current_player())
+        match &self.rejection {
+            Some(rejection) => format!("{}: {action} ({rejection})", self.board.current_player()),
+            None => format!("{}: {action}", self.board.current_player()),
+        }
     }
 
     pub fn board(&self) -> &board::Board {
         &self.board
     }
 
+    /// The last turn played, with the board it was played on.
+    pub fn last_turn(&self) -> Option<(&board::Board, &Turn)> {
+        self.history.last().map(|(board, turn)| (board, turn))
+    }
+
+    /// Turns played since the start of the game.
+    pub fn turns(&self) -> impl Iterator<Item = &Turn> {
+        self.history.iter().map(|(_, turn)| turn)
+    }
+
+    pub fn initial_board(&self) -> &board::Board {
+        self.history.first().map_or(&self.board, |(board, _)| board)
+    }
+
     pub fn play(&mut self, turn: Turn) {
-        self.board = self.board.action(&turn).unwrap();
+        let board = self.board.action(&turn).unwrap();
+        self.history
+            .push((std::mem::replace(&mut self.board, board), turn));
+        self.undone.clear();
+        self.start_turn();
+    }
+
+    /// Takes back the last played turn. Returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let Some((board, turn)) = self.history.pop() else {
+            return false;
+        };
+        self.undone.push((board.clone(), turn));
+        self.board = board;
+        self.start_turn();
+        true
+    }
+
+    /// Plays again the last undone turn. Returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let Some((board, turn)) = self.undone.pop() else {
+            return false;
+        };
+        self.board = board.action(&turn).unwrap();
+        self.history.push((board, turn));
+        self.start_turn();
+        true
+    }
+
+    fn start_turn(&mut self) {
         self.current_turn = if self
             .board
             .get_tiles()
@@ -177,3 +329,110 @@ impl Game {
         self.reset_selectable();
     }
 }
+
+// What to pick after `picked` positions of a turn using the power of `god`.
+fn power_action(god: Option<God>, picked: usize) -> &'static str {
+    match (god, picked) {
+        (_, 0) => "Pick a worker to use your power!",
+        (Some(God::Prometheus), 1) => "Build before moving!",
+        (Some(God::Prometheus), 2) => "Move your worker, not up!",
+        (_, 1) => "Move your worker!",
+        (Some(God::Artemis), 2) => "Move again!",
+        (Some(God::Atlas), 2) => "Build a dome!",
+        (Some(God::Demeter | God::Hephaestus), 3) => "Build again!",
+        _ => "Build!",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let mut game = Game::new();
+        assert!(!game.undo());
+        game.register_selection(Position::new(1, 1));
+        game.register_selection(Position::new(3, 3));
+        game.play(Turn::Setup(Position::new(1, 3), Position::new(3, 1)));
+        game.register_selection(Position::new(1, 1));
+        game.register_selection(Position::new(2, 2));
+        game.register_selection(Position::new(2, 3));
+        let played = game.board().clone();
+
+        assert!(game.undo());
+        assert_eq!(game.board().current_player(), Player::Player1);
+        assert_eq!(game.selected(), PositionSet::new());
+        assert!(game.selectable().contains(Position::new(1, 1)));
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(!game.undo());
+        assert_eq!(game.selectable(), game.board().size.positions());
+
+        while game.redo() {}
+        assert_eq!(format!("{:?}", game.board()), format!("{played:?}"));
+
+        game.undo();
+        game.play(Turn::MoveBuild {
+            start: Position::new(3, 3),
+            end: Position::new(4, 4),
+            build: Position::new(4, 3),
+        });
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn human_power_turns() {
+        let board = board::Board::from_fen("0a1000/00000/000a00/00000/0b0b000 a Atlas/-").unwrap();
+        let mut game = Game::from_board(board.clone());
+        game.register_selection(Position::new(0, 0));
+        game.toggle_power();
+        assert!(game.using_power());
+        assert_eq!(game.next_action(), "Player 1: Move your worker!");
+        game.register_selection(Position::new(0, 1));
+        game.register_selection(Position::new(0, 2));
+        assert_eq!(
+            game.turns().last(),
+            Some(&Turn::DomeBuild {
+                start: Position::new(0, 0),
+                end: Position::new(0, 1),
+                build: Position::new(0, 2),
+            })
+        );
+
+        // Prometheus builds first, the worker then not moving up.
+        let board =
+            board::Board::from_fen("0a1000/00000/000a00/00000/0b0b000 a Prometheus/-").unwrap();
+        let mut game = Game::from_board(board);
+        game.toggle_power();
+        game.register_selection(Position::new(0, 0));
+        game.register_selection(Position::new(1, 0));
+        assert!(!game.selectable().contains(Position::new(0, 1)));
+        game.register_selection(Position::new(1, 1));
+        game.register_selection(Position::new(1, 0));
+        assert_eq!(game.turns().last().unwrap().to_string(), "a2^a1-b2^a2");
+
+        // Without a power making turns of its own, the turn stays as it was.
+        let mut game = Game::from_board(
+            board::Board::from_fen("0a1000/00000/000a00/00000/0b0b000 a Pan/-").unwrap(),
+        );
+        game.toggle_power();
+        assert!(!game.using_power());
+        assert!(
+            game.next_action()
+                .ends_with("(no turn uses a god power from here)")
+        );
+    }
+
+    #[test]
+    fn human_final_move() {
+        let board = board::Board::from_fen("0a0000/02a300/00000/00000/0b0b000 a").unwrap();
+        let mut game = Game::from_board(board);
+        game.register_selection(Position::new(1, 1));
+        assert_eq!(game.winner(), None);
+        game.register_selection(Position::new(1, 2));
+        assert_eq!(game.winner(), Some(Player::Player1));
+        assert_eq!(game.turns().count(), 1);
+        assert!(game.selectable().is_empty());
+    }
+}
@@ -0,0 +1,383 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{
+    board::Board,
+    prelude::{Position, PositionIterator, PositionSet, Symmetry},
+    turn::Turn,
+};
+
+/// Enough moves for the setup of the largest board.
+pub const MAX_MOVES: usize = 4096;
+
+// Turns starting with the same worker step fit in a chunk of the generator.
+const CHUNK: usize = 128;
+
+const MOVE_BUILD: u16 = 0;
+const FINAL_MOVE: u16 = 1;
+const DOUBLE_MOVE: u16 = 2;
+const DOUBLE_MOVE_WIN: u16 = 3;
+const DOUBLE_BUILD: u16 = 4;
+const DOME_BUILD: u16 = 5;
+const BUILD_MOVE_BUILD: u16 = 6;
+const SETUP: u16 = 7;
+
+/// A turn packed in 16 bits, relative to the workers of the player to move.
+///
+/// The top 3 bits are the kind of turn. Setups then hold the indices of both
+/// positions on 6 bits each. Other turns hold which worker moves on bit 0, the
+/// direction of its move on bits 1 to 3, the direction of the build from where
+/// it ends on bits 4 to 6, and on bits 7 to 9 the direction of the step gods
+/// add: Artemis' second move, the second build of Demeter and Hephaestus, or
+/// the first build of Prometheus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    /// Packs `turn`, played by the player whose workers are `workers`.
+    pub fn encode(turn: &Turn, workers: PositionSet) -> Self {
+        let step = |kind: u16, start: Position, end: Position, build: u16, extra: u16| {
+            // The second worker is the one with the higher index.
+            let worker = workers.into_iter().any(|other| other < start) as u16;
+            Self(kind << 12 | worker | start.direction(end) << 1 | build << 4 | extra << 7)
+        };
+        match *turn {
+            Turn::Setup(first, second) => {
+                Self(SETUP << 12 | first.index() as u16 | (second.index() as u16) << 6)
+            }
+            Turn::MoveBuild { start, end, build } => {
+                step(MOVE_BUILD, start, end, end.direction(build), 0)
+            }
+            Turn::FinalMove { start, end } => step(FINAL_MOVE, start, end, 0, 0),
+            Turn::DoubleMove {
+                start,
+                middle,
+                end,
+                build,
+            } => match build {
+                Some(build) => step(
+                    DOUBLE_MOVE,
+                    start,
+                    middle,
+                    end.direction(build),
+                    middle.direction(end),
+                ),
+                None => step(DOUBLE_MOVE_WIN, start, middle, 0, middle.direction(end)),
+            },
+            Turn::DoubleBuild {
+                start,
+                end,
+                build,
+                second_build,
+            } => step(
+                DOUBLE_BUILD,
+                start,
+                end,
+                end.direction(build),
+                end.direction(second_build),
+            ),
+            Turn::DomeBuild { start, end, build } => {
+                step(DOME_BUILD, start, end, end.direction(build), 0)
+            }
+            Turn::BuildMoveBuild {
+                first_build,
+                start,
+                end,
+                build,
+            } => step(
+                BUILD_MOVE_BUILD,
+                start,
+                end,
+                end.direction(build),
+                start.direction(first_build),
+            ),
+        }
+    }
+
+    /// Unpacks the move for the player whose workers are `workers`, `None` if
+    /// it leaves the largest board.
+    pub fn decode(self, workers: PositionSet) -> Option<Turn> {
+        let kind = self.0 >> 12;
+        if kind == SETUP {
+            return Some(Turn::Setup(
+                Position::from_index((self.0 & 0b11_1111) as usize),
+                Position::from_index((self.0 >> 6 & 0b11_1111) as usize),
+            ));
+        }
+        let field = |shift: u16| self.0 >> shift & 0b111;
+        let start = if self.0 & 1 == 0 {
+            workers.into_iter().min()?
+        } else {
+            workers.into_iter().max()?
+        };
+        let end = start.step(field(1))?;
+        Some(match kind {
+            MOVE_BUILD => Turn::MoveBuild {
+                start,
+                end,
+                build: end.step(field(4))?,
+            },
+            FINAL_MOVE => Turn::FinalMove { start, end },
+            DOUBLE_MOVE | DOUBLE_MOVE_WIN => {
+                let (middle, end) = (end, end.step(field(7))?);
+                Turn::DoubleMove {
+                    start,
+                    middle,
+                    end,
+                    build: match kind {
+                        DOUBLE_MOVE => Some(end.step(field(4))?),
+                        _ => None,
+                    },
+                }
+            }
+            DOUBLE_BUILD => Turn::DoubleBuild {
+                start,
+                end,
+                build: end.step(field(4))?,
+                second_build: end.step(field(7))?,
+            },
+            DOME_BUILD => Turn::DomeBuild {
+                start,
+                end,
+                build: end.step(field(4))?,
+            },
+            _ => Turn::BuildMoveBuild {
+                first_build: start.step(field(7))?,
+                start,
+                end,
+                build: end.step(field(4))?,
+            },
+        })
+    }
+
+    /// Whether the move wins the game, like `Turn::is_final`.
+    pub fn is_final(self) -> bool {
+        matches!(self.0 >> 12, FINAL_MOVE | DOUBLE_MOVE_WIN)
+    }
+}
+
+/// Moves stored inline, up to `N` of them.
+#[derive(Clone, Debug)]
+pub struct MoveList<const N: usize = MAX_MOVES> {
+    moves: [Move; N],
+    len: usize,
+}
+
+impl<const N: usize> MoveList<N> {
+    pub fn new() -> Self {
+        Self {
+            moves: [Move::default(); N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for MoveList<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for MoveList<N> {
+    type Target = [Move];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl<const N: usize> DerefMut for MoveList<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<const N: usize> FromIterator<Move> for MoveList<N> {
+    fn from_iter<T: IntoIterator<Item = Move>>(iter: T) -> Self {
+        let mut list = Self::new();
+        for mv in iter {
+            list.push(mv);
+        }
+        list
+    }
+}
+
+/// Packs the turns the board generates for the player with `workers`.
+pub struct Encoder {
+    workers: PositionSet,
+    moves: MoveList<CHUNK>,
+}
+
+impl Encoder {
+    pub fn push(&mut self, turn: Turn) {
+        self.moves.push(Move::encode(&turn, self.workers));
+    }
+}
+
+/// Lazy iterator over the moves of a board, see `Board::moves`.
+pub struct Moves<'a> {
+    board: &'a Board,
+    setup: bool,
+    // Symmetries of the board during the setup, for setups to be generated once.
+    symmetries: Vec<Symmetry>,
+    // Workers left to move, or first positions left during the setup.
+    starts: PositionIterator,
+    start: Option<Position>,
+    // Prometheus' first builds and the moves left for the worker on `start`.
+    first_builds: PositionIterator,
+    targets: PositionIterator,
+    chunk: Encoder,
+    next: usize,
+}
+
+impl<'a> Moves<'a> {
+    pub(super) fn new(board: &'a Board) -> Self {
+        let workers = board.get_player_meeple(board.current_player());
+        let setup = workers.is_empty();
+        let starts = if board.won.is_some() {
+            PositionSet::new()
+        } else if setup {
+            board.empty_positions()
+        } else {
+            workers
+        };
+        Self {
+            board,
+            setup,
+            symmetries: if setup && board.won.is_none() {
+                board.symmetries()
+            } else {
+                vec![]
+            },
+            starts: starts.into_iter(),
+            start: None,
+            first_builds: PositionSet::new().into_iter(),
+            targets: PositionSet::new().into_iter(),
+            chunk: Encoder {
+                workers,
+                moves: MoveList::new(),
+            },
+            next: 0,
+        }
+    }
+
+    // Generates the next chunk of moves, returning false once there is none.
+    fn refill(&mut self) -> bool {
+        self.chunk.moves.clear();
+        self.next = 0;
+        loop {
+            if let Some(start) = self.start {
+                if self.setup {
+                    self.start = None;
+                    self.board
+                        .push_setups(start, &self.symmetries, &mut self.chunk);
+                    return true;
+                }
+                if let Some(first_build) = self.first_builds.next() {
+                    self.board
+                        .push_build_first(start, first_build, &mut self.chunk);
+                    return true;
+                }
+                if let Some(end) = self.targets.next() {
+                    self.board.push_step(start, end, &mut self.chunk);
+                    return true;
+                }
+            }
+            let Some(start) = self.starts.next() else {
+                return false;
+            };
+            self.start = Some(start);
+            if !self.setup {
+                let (first_builds, targets) = self.board.worker_steps(start);
+                self.first_builds = first_builds.into_iter();
+                self.targets = targets.into_iter();
+            }
+        }
+    }
+}
+
+impl Iterator for Moves<'_> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next == self.chunk.moves.len() {
+            if !self.refill() {
+                return None;
+            }
+        }
+        self.next += 1;
+        Some(self.chunk.moves[self.next - 1])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{
+        god::{ALL_GODS, God},
+        prelude::Construction,
+    };
+
+    #[test]
+    fn encoding_round_trips() {
+        use rand::prelude::*;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for god in ALL_GODS {
+            let mut board = Board::with_gods(Some(god), Some(God::Apollo));
+            loop {
+                let workers = board.get_player_meeple(board.current_player());
+                let moves: MoveList = board.moves().collect();
+                let turns = board.possible_move();
+                assert_eq!(moves.len(), turns.len());
+                assert_eq!(
+                    board.has_winning_turn(),
+                    turns.iter().any(Turn::is_final),
+                    "{god}"
+                );
+                for (&mv, turn) in moves.iter().zip(&turns) {
+                    assert_eq!(Move::encode(turn, workers), mv, "{god}: {turn}");
+                    assert_eq!(mv.decode(workers).as_ref(), Some(turn), "{god}: {turn}");
+                    assert_eq!(mv.is_final(), turn.is_final());
+                }
+                let Some(turn) = turns.choose(&mut rng) else {
+                    break;
+                };
+                if turn.is_final() {
+                    break;
+                }
+                board = board.action(turn).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn largest_setup() {
+        let mut board = Board::new();
+        board.size = "8x7".parse().unwrap();
+        // Pairs up to the flips and the half turn of the board.
+        assert_eq!(board.moves().count(), 412);
+        // Without symmetries, every pair is a setup.
+        board.set_construction(Position::new(0, 0), Construction::FirstLevel);
+        let moves: MoveList = board.moves().collect();
+        assert_eq!(moves.len(), 56 * 55 / 2);
+        assert!(moves.len() <= MAX_MOVES);
+    }
+
+    #[test]
+    fn stops_early() {
+        let board = Board::from_fen("00000/00a00b0/00000/00b00a0/00000 a").unwrap();
+        let mut moves = board.moves();
+        let first = moves.next().unwrap();
+        assert_eq!(board.turn(first), board.possible_move()[0]);
+        // Only the turns of the first worker step were generated.
+        assert!(moves.chunk.moves.len() < board.possible_move().len() / 4);
+    }
+}
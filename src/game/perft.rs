@@ -0,0 +1,98 @@
+use super::{board::Board, movegen::MoveList, turn::Turn};
+
+/// Counts the positions reached after exactly `depth` turns from `board`.
+///
+/// A winning turn ends the game, so it only counts as a leaf at the last depth.
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    count(&mut board.clone(), depth)
+}
+
+// Plays and takes back every turn on `board` rather than copying it.
+fn count(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return board.moves().count() as u64;
+    }
+    let moves: MoveList = board.moves().filter(|mv| !mv.is_final()).collect();
+    moves
+        .iter()
+        .map(|&mv| {
+            let undo = board.apply(&board.turn(mv)).unwrap();
+            let nodes = count(board, depth - 1);
+            board.undo(undo);
+            nodes
+        })
+        .sum()
+}
+
+/// Breaks `perft` down by first turn.
+pub fn divide(board: &Board, depth: u32) -> Vec<(Turn, u64)> {
+    board
+        .possible_move()
+        .into_iter()
+        .map(|turn| {
+            let count = match depth {
+                0 => 0,
+                1 => 1,
+                _ if turn.is_final() => 0,
+                _ => perft(&board.action(&turn).unwrap(), depth - 1),
+            };
+            (turn, count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Node counts of known positions, to check move generation refactors against.
+    const REGRESSIONS: [(&str, u32, u64); 9] = [
+        ("00000/00000/00000/00000/00000 a", 2, 9_664),
+        ("00000/00a000/00000/000a00/00000 b", 2, 20_916),
+        ("00000/00a00b0/00000/00b00a0/00000 a", 3, 426_384),
+        ("1001a0/02b100b/02310/02a120/00004 a", 3, 144_157),
+        (
+            "1001a0/02b100b/02310/02a120/00004 b Apollo/Minotaur",
+            3,
+            173_496,
+        ),
+        ("1001a0/02b100b/02310/02a120/00004 a Pan/Athena", 3, 79_975),
+        (
+            "1001a0/02b100b/02310/02a120/00004 a Artemis/Demeter",
+            2,
+            47_613,
+        ),
+        (
+            "1001a0/02b100b/02310/02a120/00004 b Atlas/Prometheus",
+            2,
+            38_269,
+        ),
+        (
+            "1001a0/02b100b/02310/02a120/00004 b Atlas/Hephaestus",
+            3,
+            811_813,
+        ),
+    ];
+
+    #[test]
+    fn regressions() {
+        for (fen, depth, expected) in REGRESSIONS {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(perft(&board, depth), expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let board = Board::from_fen("1001a0/02b100b/02310/02a120/00004 a").unwrap();
+        for depth in 1..=3 {
+            let divided = divide(&board, depth);
+            assert_eq!(divided.len(), 58);
+            let total: u64 = divided.iter().map(|(_, count)| count).sum();
+            assert_eq!(total, perft(&board, depth));
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use super::error;
+use super::{error, god::God};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Tile {
@@ -9,76 +9,290 @@ pub struct Tile {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Position(u32);
+pub struct Position(u64);
 
-// Sentinel of impossible position.
-const SENTINEL: u32 = 0b11_100000_100000_100000_100000_100000;
-fn bad_position(p: u32) -> bool {
+// Each row takes 8 bits, the last one being a sentinel separating rows.
+const ROW_STRIDE: u32 = 8;
+const SENTINEL: u64 = 0x8080_8080_8080_8080;
+const fn bad_position(p: u64) -> bool {
     p == 0 || p & SENTINEL != 0
 }
 
+// Index offsets of the neighbors, numbered as in `Position::direction`.
+const DIRECTIONS: [i32; 8] = [
+    1,
+    -1,
+    ROW_STRIDE as i32,
+    -(ROW_STRIDE as i32),
+    ROW_STRIDE as i32 - 1,
+    ROW_STRIDE as i32 + 1,
+    -(ROW_STRIDE as i32 - 1),
+    -(ROW_STRIDE as i32 + 1),
+];
+
+/// Largest board fitting in the bitboard.
+pub const MAX_ROWS: usize = 8;
+pub const MAX_COLS: usize = 7;
+
+// Marks pairs of positions that are not neighbors in `DIRECTION`.
+const NOT_ADJACENT: u8 = 8;
+
+// Neighbor masks and directions between positions, indexed by bitboard index.
+// Sentinel indices have no neighbors.
+static NEIGHBORS: [u64; 64] = {
+    let mut table = [0; 64];
+    let mut index = 0;
+    while index < 64 {
+        let mut direction = 0;
+        while direction < 8 {
+            if let Some(neighbor) = neighbor_index(index, direction) {
+                table[index] |= 1 << neighbor;
+            }
+            direction += 1;
+        }
+        index += 1;
+    }
+    table
+};
+
+static DIRECTION: [[u8; 64]; 64] = {
+    let mut table = [[NOT_ADJACENT; 64]; 64];
+    let mut index = 0;
+    while index < 64 {
+        let mut direction = 0;
+        while direction < 8 {
+            if let Some(neighbor) = neighbor_index(index, direction) {
+                table[index][neighbor] = direction as u8;
+            }
+            direction += 1;
+        }
+        index += 1;
+    }
+    table
+};
+
+const fn neighbor_index(index: usize, direction: usize) -> Option<usize> {
+    let neighbor = index as i32 + DIRECTIONS[direction];
+    if bad_position(1 << index) || neighbor < 0 || neighbor >= 64 || bad_position(1 << neighbor) {
+        return None;
+    }
+    Some(neighbor as usize)
+}
+
 impl Position {
     pub fn new(row: usize, col: usize) -> Self {
-        Self(1 << (row * 6 + col))
+        Self(1 << (row * ROW_STRIDE as usize + col))
     }
 
     pub fn row(self) -> usize {
-        (self.0.ilog2() / 6) as usize
+        (self.0.ilog2() / ROW_STRIDE) as usize
     }
 
     pub fn col(self) -> usize {
-        (self.0.ilog2() % 6) as usize
+        (self.0.ilog2() % ROW_STRIDE) as usize
+    }
+
+    /// Index of the position in the underlying bitboard.
+    pub fn index(self) -> usize {
+        self.0.trailing_zeros() as usize
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        Self(1 << index)
+    }
+
+    /// Number in `0..8` of the direction going to the neighbor `to`.
+    pub fn direction(self, to: Position) -> u16 {
+        let direction = DIRECTION[self.index()][to.index()];
+        assert!(direction != NOT_ADJACENT, "not a neighbor");
+        direction as u16
+    }
+
+    /// The neighbor in `direction`, if it is within the largest board.
+    pub fn step(self, direction: u16) -> Option<Position> {
+        neighbor_index(self.index(), direction as usize).map(Self::from_index)
     }
 
+    /// Neighbors within the largest board, callers keep the ones on their board.
     pub fn get_neighbors(self) -> PositionSet {
-        [
-            self.0 << 1,
-            self.0 >> 1,
-            self.0 << 6,
-            self.0 >> 6,
-            self.0 << 5,
-            self.0 << 7,
-            self.0 >> 5,
-            self.0 >> 7,
-        ]
-        .iter()
-        .filter(|&&bm| !bad_position(bm))
-        .map(|&bm| Self(bm))
-        .collect()
+        PositionSet(NEIGHBORS[self.index()])
     }
 
     pub fn are_neighbors(p1: Position, p2: Position) -> bool {
-        let p1 = p1.0.ilog2();
-        let p2 = p2.0.ilog2();
-        let diff = p1.abs_diff(p2);
-        matches!(diff, 1 | 5 | 6 | 7)
+        DIRECTION[p1.index()][p2.index()] != NOT_ADJACENT
+    }
+
+    /// The position `symmetry` maps this one to, on a board of `size`.
+    pub fn transform(self, symmetry: Symmetry, size: Size) -> Position {
+        let (row, col) = (self.row(), self.col());
+        let (last_row, last_col) = (size.rows - 1, size.cols - 1);
+        let (row, col) = match symmetry {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, last_row - row),
+            Symmetry::Rotate180 => (last_row - row, last_col - col),
+            Symmetry::Rotate270 => (last_col - col, row),
+            Symmetry::FlipRows => (last_row - row, col),
+            Symmetry::FlipCols => (row, last_col - col),
+            Symmetry::Transpose => (col, row),
+            Symmetry::AntiTranspose => (last_col - col, last_row - row),
+        };
+        Position::new(row, col)
+    }
+
+    /// The position one step further in the direction going from `from` to `to`,
+    /// if it is within the largest board.
+    pub fn beyond(from: Position, to: Position) -> Option<Position> {
+        let next = if to.0 > from.0 {
+            to.0 << (to.0.ilog2() - from.0.ilog2())
+        } else {
+            to.0 >> (from.0.ilog2() - to.0.ilog2())
+        };
+        (!bad_position(next)).then_some(Self(next))
+    }
+
+    pub fn up(&mut self, size: Size) {
+        *self = Self::new((self.row() + size.rows - 1) % size.rows, self.col());
     }
 
-    pub fn up(&mut self) {
-        let new = self.0 >> 6;
-        self.0 = if bad_position(new) { self.0 << 24 } else { new };
+    pub fn down(&mut self, size: Size) {
+        *self = Self::new((self.row() + 1) % size.rows, self.col());
     }
 
-    pub fn down(&mut self) {
-        let new = self.0 << 6;
-        self.0 = if bad_position(new) { self.0 >> 24 } else { new };
+    pub fn left(&mut self, size: Size) {
+        *self = Self::new(self.row(), (self.col() + size.cols - 1) % size.cols);
     }
 
-    pub fn left(&mut self) {
-        let new = self.0 >> 1;
-        self.0 = if bad_position(new) { self.0 << 4 } else { new };
+    pub fn right(&mut self, size: Size) {
+        *self = Self::new(self.row(), (self.col() + 1) % size.cols);
     }
+}
 
-    pub fn right(&mut self) {
-        let new = self.0 << 1;
-        self.0 = if bad_position(new) { self.0 >> 4 } else { new };
+// Algebraic notation: columns are letters from `a`, rows are numbers from `1`.
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", (b'a' + self.col() as u8) as char, self.row() + 1)
     }
 }
 
+impl FromStr for Position {
+    type Err = error::NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || error::NotationError::InvalidPosition(s.to_string());
+        let mut chars = s.chars();
+        let col = chars
+            .next()
+            .and_then(|c| c.is_ascii_lowercase().then(|| c as usize - 'a' as usize))
+            .ok_or_else(invalid)?;
+        let row = chars
+            .as_str()
+            .parse::<usize>()
+            .map_err(|_| invalid())?
+            .checked_sub(1)
+            .ok_or_else(invalid)?;
+        if row >= MAX_ROWS || col >= MAX_COLS {
+            return Err(invalid());
+        }
+        Ok(Position::new(row, col))
+    }
+}
+
+/// Dimensions of a board, 5x5 by default.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct PositionSet(u32);
+pub struct Size {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Size {
+    /// All the positions of a board of this size.
+    pub fn positions(self) -> PositionSet {
+        let row = (1 << self.cols) - 1;
+        PositionSet((0..self.rows).fold(0, |acc, r| acc | row << (r * ROW_STRIDE as usize)))
+    }
+}
+
+impl Default for Size {
+    fn default() -> Self {
+        Self { rows: 5, cols: 5 }
+    }
+}
+
+impl Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
+impl FromStr for Size {
+    type Err = error::NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || error::NotationError::InvalidSize(s.to_string());
+        let (rows, cols) = s.split_once('x').ok_or_else(invalid)?;
+        let size = Self {
+            rows: rows.parse().map_err(|_| invalid())?,
+            cols: cols.parse().map_err(|_| invalid())?,
+        };
+        if !(3..=MAX_ROWS).contains(&size.rows) || !(3..=MAX_COLS).contains(&size.cols) {
+            return Err(invalid());
+        }
+        Ok(size)
+    }
+}
+
+/// One of the rotations and reflections of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    /// A quarter turn clockwise.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Swaps the first and last rows.
+    FlipRows,
+    /// Swaps the first and last columns.
+    FlipCols,
+    /// Mirrors along the diagonal going through a1.
+    Transpose,
+    /// Mirrors along the other diagonal.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipRows,
+        Symmetry::FlipCols,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Whether a board of `size` keeps its size, quarter turns and diagonals
+    /// only applying to square boards.
+    pub fn preserves(self, size: Size) -> bool {
+        size.rows == size.cols
+            || matches!(
+                self,
+                Symmetry::Identity | Symmetry::Rotate180 | Symmetry::FlipRows | Symmetry::FlipCols
+            )
+    }
+
+    /// The symmetry undoing this one.
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+}
 
-pub const ALL_POSITIONS: PositionSet = PositionSet(u32::MAX ^ SENTINEL);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PositionSet(u64);
 
 impl PositionSet {
     pub fn intersection(self, other: Self) -> Self {
@@ -116,6 +330,12 @@ impl PositionSet {
     pub fn remove(&mut self, position: Position) {
         self.0 = (self.0 | position.0) ^ position.0;
     }
+
+    pub fn transform(self, symmetry: Symmetry, size: Size) -> PositionSet {
+        self.into_iter()
+            .map(|position| position.transform(symmetry, size))
+            .collect()
+    }
 }
 
 impl<const N: usize> From<[Position; N]> for PositionSet {
@@ -136,12 +356,12 @@ impl IntoIterator for PositionSet {
 
 impl FromIterator<Position> for PositionSet {
     fn from_iter<T: IntoIterator<Item = Position>>(iter: T) -> Self {
-        PositionSet(iter.into_iter().fold(0u32, |acc, element| acc | element.0))
+        PositionSet(iter.into_iter().fold(0u64, |acc, element| acc | element.0))
     }
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct PositionIterator(u32, u32);
+pub struct PositionIterator(u64, u64);
 
 impl Iterator for PositionIterator {
     type Item = Position;
@@ -177,7 +397,7 @@ impl Default for Tile {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Construction {
     GroundLevel,
     FirstLevel,
@@ -186,15 +406,46 @@ pub enum Construction {
     Dome,
 }
 
+impl Display for Construction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Construction::GroundLevel => "ground level",
+            Construction::FirstLevel => "first level",
+            Construction::SecondLevel => "second level",
+            Construction::ThirdLevel => "third level",
+            Construction::Dome => "dome",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Pieces left to build with, when they are limited like in the physical game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Supply(pub [u8; 4]);
+
+impl Supply {
+    /// 22 first level, 18 second level and 14 third level blocks, and 18 domes.
+    pub const FULL: Supply = Supply([22, 18, 14, 18]);
+
+    /// Pieces left of `piece`, which must not be the ground level.
+    pub fn remaining(self, piece: Construction) -> u8 {
+        self.0[piece as usize - 1]
+    }
+
+    pub fn take(&mut self, piece: Construction) {
+        self.0[piece as usize - 1] -= 1;
+    }
+}
+
 impl Construction {
-    pub fn build(self) -> error::Result<Construction> {
+    pub fn build(self) -> Option<Construction> {
         use Construction::*;
         match self {
-            GroundLevel => Ok(FirstLevel),
-            FirstLevel => Ok(SecondLevel),
-            SecondLevel => Ok(ThirdLevel),
-            ThirdLevel => Ok(Dome),
-            Dome => Err(error::GameError::InvalidMove),
+            GroundLevel => Some(FirstLevel),
+            FirstLevel => Some(SecondLevel),
+            SecondLevel => Some(ThirdLevel),
+            ThirdLevel => Some(Dome),
+            Dome => None,
         }
     }
 
@@ -209,26 +460,65 @@ impl Construction {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Player {
     Player1,
     Player2,
+    Player3,
+    Player4,
 }
 
 impl Player {
-    pub fn other_player(&self) -> Player {
-        match self {
-            Player::Player1 => Player::Player2,
-            Player::Player2 => Player::Player1,
-        }
-    }
+    /// All the players in turn order, games use the first `Board::player_count`.
+    pub const ALL: [Player; 4] = [
+        Player::Player1,
+        Player::Player2,
+        Player::Player3,
+        Player::Player4,
+    ];
 }
 
 impl Display for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Player::Player1 => write!(f, "Player 1"),
-            Player::Player2 => write!(f, "Player 2"),
+        write!(f, "Player {}", *self as usize + 1)
+    }
+}
+
+/// Why a game was won.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinReason {
+    /// A worker moved up to the third floor.
+    ThirdFloor,
+    /// A god's own winning condition, such as Pan moving down two levels.
+    Power(God),
+    /// The other players had no legal turn left.
+    Blocked,
+    /// The other players resigned.
+    Resignation,
+    /// The other players ran out of time.
+    Time,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub winner: Player,
+    /// The winner's partner in a team game.
+    pub teammate: Option<Player>,
+    pub reason: WinReason,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.teammate {
+            Some(teammate) => write!(f, "{} and {teammate}", self.winner)?,
+            None => write!(f, "{}", self.winner)?,
+        }
+        match self.reason {
+            WinReason::ThirdFloor => write!(f, " won by reaching the third floor"),
+            WinReason::Power(god) => write!(f, " won with the power of {god}"),
+            WinReason::Blocked => write!(f, " won by blocking every opponent"),
+            WinReason::Resignation => write!(f, " won by resignation"),
+            WinReason::Time => write!(f, " won on time"),
         }
     }
 }
@@ -315,24 +605,153 @@ mod tests {
         }
     }
 
+    // The shift-based neighbors the tables replaced.
+    fn shifted_neighbors(p: Position) -> PositionSet {
+        [
+            p.0 << 1,
+            p.0 >> 1,
+            p.0 << ROW_STRIDE,
+            p.0 >> ROW_STRIDE,
+            p.0 << (ROW_STRIDE - 1),
+            p.0 << (ROW_STRIDE + 1),
+            p.0 >> (ROW_STRIDE - 1),
+            p.0 >> (ROW_STRIDE + 1),
+        ]
+        .iter()
+        .filter(|&&bm| !bad_position(bm))
+        .map(|&bm| Position(bm))
+        .collect()
+    }
+
+    fn shifted_are_neighbors(p1: Position, p2: Position) -> bool {
+        let diff = p1.0.ilog2().abs_diff(p2.0.ilog2());
+        diff == 1 || diff.abs_diff(ROW_STRIDE) == 1 || diff == ROW_STRIDE
+    }
+
+    #[test]
+    fn tables_match_shifts() {
+        let largest = Size {
+            rows: MAX_ROWS,
+            cols: MAX_COLS,
+        };
+        for pos1 in largest.positions() {
+            assert_eq!(pos1.get_neighbors(), shifted_neighbors(pos1), "{pos1}");
+            for pos2 in largest.positions() {
+                let adjacent = shifted_are_neighbors(pos1, pos2);
+                assert_eq!(
+                    Position::are_neighbors(pos1, pos2),
+                    adjacent,
+                    "{pos1} {pos2}"
+                );
+                if adjacent {
+                    assert_eq!(pos1.step(pos1.direction(pos2)), Some(pos2));
+                }
+            }
+            for direction in 0..8 {
+                if let Some(neighbor) = pos1.step(direction) {
+                    assert_eq!(pos1.direction(neighbor), direction);
+                    assert!(shifted_neighbors(pos1).contains(neighbor));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn beyond() {
+        assert_eq!(
+            Position::beyond(Position::new(1, 1), Position::new(2, 2)),
+            Some(Position::new(3, 3))
+        );
+        assert_eq!(
+            Position::beyond(Position::new(2, 1), Position::new(2, 0)),
+            None
+        );
+        assert_eq!(
+            Position::beyond(Position::new(3, 3), Position::new(4, 4)),
+            Some(Position::new(5, 5))
+        );
+        assert_eq!(
+            Position::beyond(Position::new(6, 5), Position::new(7, 6)),
+            None
+        );
+        assert_eq!(
+            Position::beyond(Position::new(2, 5), Position::new(2, 6)),
+            None
+        );
+        assert_eq!(
+            Position::beyond(Position::new(1, 3), Position::new(0, 4)),
+            None
+        );
+    }
+
+    #[test]
+    fn notation() {
+        let largest = Size {
+            rows: MAX_ROWS,
+            cols: MAX_COLS,
+        };
+        for position in largest.positions() {
+            assert_eq!(position.to_string().parse(), Ok(position));
+        }
+        assert_eq!("a1".parse(), Ok(Position::new(0, 0)));
+        assert_eq!("c4".parse(), Ok(Position::new(3, 2)));
+        assert_eq!("g8".parse(), Ok(Position::new(7, 6)));
+        for invalid in ["", "a", "h1", "a0", "a9", "A1", "a1b"] {
+            assert!(invalid.parse::<Position>().is_err(), "{invalid}");
+        }
+
+        assert_eq!("6x7".parse(), Ok(Size { rows: 6, cols: 7 }));
+        assert_eq!(Size::default().to_string(), "5x5");
+        for invalid in ["", "5", "5x", "2x5", "5x8", "9x5", "5X5"] {
+            assert!(invalid.parse::<Size>().is_err(), "{invalid}");
+        }
+    }
+
     #[test]
     fn moves() {
-        for r1 in 0..5 {
-            for c1 in 0..5 {
-                let pos1 = Position::new(r1, c1);
+        for size in [Size::default(), Size { rows: 8, cols: 6 }] {
+            for pos1 in size.positions() {
                 let mut p = pos1;
-                p.up();
-                p.down();
+                p.up(size);
+                p.down(size);
                 assert_eq!(pos1, p);
-                p.left();
-                p.right();
+                p.left(size);
+                p.right(size);
                 assert_eq!(pos1, p);
-                p.up();
-                p.left();
-                p.down();
-                p.right();
+                p.up(size);
+                p.left(size);
+                p.down(size);
+                p.right(size);
                 assert_eq!(pos1, p);
             }
         }
+        let mut corner = Position::new(0, 0);
+        corner.up(Size::default());
+        corner.left(Size::default());
+        assert_eq!(corner, Position::new(4, 4));
+    }
+
+    #[test]
+    fn symmetries() {
+        for size in [Size::default(), Size { rows: 8, cols: 6 }] {
+            let positions = size.positions();
+            for symmetry in Symmetry::ALL.into_iter().filter(|s| s.preserves(size)) {
+                assert_eq!(positions.transform(symmetry, size), positions);
+                for position in positions {
+                    let image = position.transform(symmetry, size);
+                    assert_eq!(image.transform(symmetry.inverse(), size), position);
+                }
+            }
+        }
+        let (corner, size) = (Position::new(0, 0), Size::default());
+        assert_eq!(
+            corner.transform(Symmetry::Rotate90, size),
+            Position::new(0, 4)
+        );
+        assert_eq!(
+            corner.transform(Symmetry::AntiTranspose, size),
+            Position::new(4, 4)
+        );
+        assert!(!Symmetry::Transpose.preserves(Size { rows: 8, cols: 6 }));
     }
 }
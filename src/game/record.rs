@@ -0,0 +1,256 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    Game,
+    board::Board,
+    error::NotationError,
+    god::God,
+    prelude::{Player, Size, Supply},
+    turn::Turn,
+};
+
+/// A game written as headers followed by one turn per line:
+///
+/// ```text
+/// [Player1 "Human"]
+/// [Player2 "Greedy hill climber"]
+/// [Date "2025-06-01"]
+/// [Result "1-0"]
+///
+/// S c3 d3
+/// S b2 b4
+/// c3-d4^e5
+/// ```
+///
+/// `Player1God` to `Player4God` headers record god powers, `Players` the number
+/// of players other than 2, `Size` the board dimensions other than `5x5` and
+/// `Supply "limited"` the finite piece supply.
+#[derive(Clone, Debug, Default)]
+pub struct Record {
+    pub headers: Vec<(String, String)>,
+    pub turns: Vec<Turn>,
+}
+
+impl Record {
+    /// Records the turns played so far in `game`, dated today.
+    pub fn from_game(game: &Game) -> Self {
+        let mut record = Self {
+            headers: vec![],
+            turns: game.turns().cloned().collect(),
+        };
+        record.set_header("Date", &today());
+        let board = game.initial_board();
+        if board.player_count != 2 {
+            record.set_header("Players", &board.player_count.to_string());
+        }
+        for player in board.players() {
+            if let Some(god) = board.get_player_god(player) {
+                record.set_header(&god_header(player), &god.to_string());
+            }
+        }
+        if board.size != Size::default() {
+            record.set_header("Size", &board.size.to_string());
+        }
+        if board.supply.is_some() {
+            record.set_header("Supply", "limited");
+        }
+        record
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        match self.headers.iter_mut().find(|(header, _)| header == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Sets the `Result` header, with one score per side: `1-0` when the first
+    /// player (and in four-player games their teammate, the third player) won,
+    /// `0-0-1` when the third of three players won.
+    pub fn set_result(&mut self, winner: Option<Player>) {
+        let sides = match self.header("Players") {
+            Some("3") => 3,
+            _ => 2,
+        };
+        let result = match winner {
+            Some(winner) => (0..sides)
+                .map(|side| {
+                    if winner as usize % sides == side {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("-"),
+            None => "*".to_string(),
+        };
+        self.set_header("Result", &result);
+    }
+
+    pub fn initial_board(&self) -> Result<Board, NotationError> {
+        let players = match self.header("Players") {
+            None => 2,
+            Some(count @ ("2" | "3" | "4")) => count.parse().unwrap(),
+            Some(count) => return Err(NotationError::InvalidHeader(format!("Players {count}"))),
+        };
+        let gods = Player::ALL[..players]
+            .iter()
+            .map(|&player| {
+                self.header(&god_header(player))
+                    .map(str::parse::<God>)
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let supply = match self.header("Supply") {
+            None => None,
+            Some("limited") => Some(Supply::FULL),
+            Some(supply) => return Err(NotationError::InvalidHeader(format!("Supply {supply}"))),
+        };
+        Ok(Board {
+            size: self
+                .header("Size")
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            supply,
+            ..Board::with_players(&gods)
+        })
+    }
+
+    /// Plays all the recorded turns, checking that they are legal.
+    pub fn replay(&self) -> Result<Game, NotationError> {
+        let mut game = Game::from_board(self.initial_board()?);
+        for (index, turn) in self.turns.iter().enumerate() {
+            game.board()
+                .action(turn)
+                .map_err(|error| NotationError::IllegalTurn(index + 1, error))?;
+            game.play(turn.clone());
+        }
+        Ok(game)
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in &self.headers {
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        writeln!(f)?;
+        for turn in &self.turns {
+            writeln!(f, "{turn}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Record {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut record = Self::default();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some(header) = line.strip_prefix('[') else {
+                record.turns.push(line.parse()?);
+                continue;
+            };
+            let invalid = || NotationError::InvalidHeader(line.to_string());
+            let (name, value) = header
+                .strip_suffix(']')
+                .and_then(|header| header.split_once(' '))
+                .ok_or_else(invalid)?;
+            let value = value
+                .trim()
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .ok_or_else(invalid)?;
+            record.set_header(name, value);
+        }
+        Ok(record)
+    }
+}
+
+fn god_header(player: Player) -> String {
+    format!("Player{}God", player as usize + 1)
+}
+
+// Today's date as `YYYY-MM-DD`, in UTC.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    // Converts days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replay() {
+        let text = "[Player1 \"Human\"]\n[Player1God \"Pan\"]\n\nS c3 d3\nS b2 b4\nc3-c4^c5\n";
+        let record: Record = text.parse().unwrap();
+        assert_eq!(record.header("Player1"), Some("Human"));
+        assert_eq!(record.to_string(), text);
+
+        let game = record.replay().unwrap();
+        assert_eq!(game.turns().count(), 3);
+        assert_eq!(game.board().get_player_god(Player::Player1), Some(God::Pan));
+
+        let record: Record = "[Size \"6x7\"]\n[Supply \"limited\"]\n\nS f6 g6\n"
+            .parse()
+            .unwrap();
+        let board = record.replay().unwrap().initial_board().clone();
+        assert_eq!(board.size, Size { rows: 6, cols: 7 });
+        assert_eq!(board.supply, Some(Supply::FULL));
+        assert_eq!(
+            Record::from_game(&Game::from_board(board)).header("Size"),
+            Some("6x7")
+        );
+
+        let mut record: Record = "[Players \"3\"]\n[Player3God \"Atlas\"]\n\nS a1 a2\n"
+            .parse()
+            .unwrap();
+        let board = record.initial_board().unwrap();
+        assert_eq!(board.player_count, 3);
+        assert_eq!(board.get_player_god(Player::Player3), Some(God::Atlas));
+        record.set_result(Some(Player::Player3));
+        assert_eq!(record.header("Result"), Some("0-0-1"));
+        record.set_header("Players", "4");
+        record.set_result(Some(Player::Player3));
+        assert_eq!(record.header("Result"), Some("1-0"));
+
+        let illegal: Record = "S c3 d3\nS c3 b4\n".parse().unwrap();
+        assert!(matches!(
+            illegal.replay(),
+            Err(NotationError::IllegalTurn(2, _))
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use super::{
+    Game,
+    clock::Clock,
+    error::NotationError,
+    prelude::{Player, Position},
+    record::Record,
+};
+
+pub const SAVE_VERSION: u32 = 1;
+
+/// A game in progress, written as `pompei-save <version>` followed by its
+/// record. Besides the record headers, `Player1Spec` to `Player4Spec` hold the
+/// command line specs of the players, `Selection` the positions picked so far
+/// in the current turn, after `power` for a turn using a god power, and
+/// `TimeControl` and `Clock` the remaining seconds of every player in timed
+/// games.
+///
+/// Files without the version line are plain game records, which load too.
+#[derive(Clone, Debug, Default)]
+pub struct Save {
+    pub record: Record,
+}
+
+impl Save {
+    pub fn new(game: &Game, specs: &[String], clock: Option<&Clock>) -> Self {
+        let mut record = Record::from_game(game);
+        for (index, spec) in specs.iter().enumerate() {
+            record.set_header(&format!("Player{}Spec", index + 1), spec);
+        }
+        let selection = game.pending_selections();
+        if !selection.is_empty() {
+            let selection = join(selection.iter());
+            let selection = if game.using_power() {
+                format!("power {selection}")
+            } else {
+                selection
+            };
+            record.set_header("Selection", &selection);
+        }
+        if let Some(clock) = clock {
+            let now = Instant::now();
+            record.set_header("TimeControl", &clock.control.to_string());
+            record.set_header(
+                "Clock",
+                &join(
+                    game.board()
+                        .players()
+                        .map(|player| format!("{:.1}", clock.remaining(player, now).as_secs_f64())),
+                ),
+            );
+        }
+        Self { record }
+    }
+
+    pub fn spec(&self, player: Player) -> Option<&str> {
+        self.record
+            .header(&format!("Player{}Spec", player as usize + 1))
+    }
+
+    /// Replays the saved turns and the selections of the current one.
+    pub fn game(&self) -> Result<Game, NotationError> {
+        let mut game = self.record.replay()?;
+        let selection = self.record.header("Selection").unwrap_or_default();
+        let mut positions = selection.split_whitespace().peekable();
+        if positions.next_if_eq(&"power").is_some() {
+            game.toggle_power();
+        }
+        for position in positions {
+            let position: Position = position.parse()?;
+            if !game.selectable().contains(position) {
+                return Err(NotationError::InvalidHeader(format!(
+                    "Selection {selection}"
+                )));
+            }
+            game.register_selection(position);
+        }
+        Ok(game)
+    }
+
+    pub fn clock(&self) -> Result<Option<Clock>, NotationError> {
+        let Some(control) = self.record.header("TimeControl") else {
+            return Ok(None);
+        };
+        let mut clock = Clock::new(control.parse()?);
+        let times = self.record.header("Clock").unwrap_or_default();
+        for (player, time) in Player::ALL.into_iter().zip(times.split_whitespace()) {
+            let time = time
+                .parse()
+                .ok()
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .ok_or_else(|| NotationError::InvalidHeader(format!("Clock {times}")))?;
+            clock.set_remaining(player, time);
+        }
+        Ok(Some(clock))
+    }
+}
+
+fn join(items: impl Iterator<Item = impl ToString>) -> String {
+    items
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Display for Save {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "pompei-save {SAVE_VERSION}")?;
+        write!(f, "{}", self.record)
+    }
+}
+
+impl FromStr for Save {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (version, record) = match s.strip_prefix("pompei-save ") {
+            Some(rest) => rest.split_once('\n').unwrap_or((rest, "")),
+            None => ("0", s),
+        };
+        // Newer versions add their own arms, converting older saves if needed.
+        match version.trim() {
+            "0" | "1" => Ok(Self {
+                record: record.parse()?,
+            }),
+            version => Err(NotationError::UnsupportedVersion(version.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_and_resume() {
+        let mut game = "[Player1God \"Pan\"]\n\nS c3 d3\nS b2 b4\n"
+            .parse::<Record>()
+            .unwrap()
+            .replay()
+            .unwrap();
+        game.register_selection(Position::new(2, 2));
+        let mut clock = Clock::new("5+3".parse().unwrap());
+        clock.set_remaining(Player::Player2, Duration::from_millis(12_500));
+        let specs = ["human".to_string(), "alphabeta:500".to_string()];
+        let text = Save::new(&game, &specs, Some(&clock)).to_string();
+        assert!(text.starts_with("pompei-save 1\n"));
+
+        let save: Save = text.parse().unwrap();
+        assert_eq!(save.spec(Player::Player2), Some("alphabeta:500"));
+        assert_eq!(save.spec(Player::Player3), None);
+        let resumed = save.game().unwrap();
+        assert_eq!(resumed.board(), game.board());
+        assert_eq!(resumed.turns().count(), 2);
+        assert_eq!(resumed.pending_selections(), [Position::new(2, 2)]);
+        let clock = save.clock().unwrap().unwrap();
+        assert_eq!(clock.control, "5+3".parse().unwrap());
+        let now = Instant::now();
+        assert_eq!(
+            clock.remaining(Player::Player1, now),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            clock.remaining(Player::Player2, now),
+            Duration::from_millis(12_500)
+        );
+
+        let record: Save = "S c3 d3\n".parse().unwrap();
+        assert_eq!(record.game().unwrap().turns().count(), 1);
+        assert!(record.clock().unwrap().is_none());
+        assert!(matches!(
+            "pompei-save 2\n\nS c3 d3\n".parse::<Save>(),
+            Err(NotationError::UnsupportedVersion(version)) if version == "2"
+        ));
+    }
+
+    #[test]
+    fn resume_power_turn() {
+        let mut game = "[Player1God \"Artemis\"]\n\nS c3 d3\nS b2 b4\n"
+            .parse::<Record>()
+            .unwrap()
+            .replay()
+            .unwrap();
+        game.toggle_power();
+        game.register_selection(Position::new(2, 2));
+        let save = Save::new(&game, &[], None);
+        assert_eq!(save.record.header("Selection"), Some("power c3"));
+        let resumed = save.game().unwrap();
+        assert!(resumed.using_power());
+        assert_eq!(resumed.pending_selections(), [Position::new(2, 2)]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fmt::Display, str::FromStr};
+
 use super::*;
 
 #[derive(Debug)]
@@ -7,6 +9,8 @@ pub enum PartialTurn {
     Move(Position, Position),
     NothingSetup,
     PartialSetup(Position),
+    /// Positions picked so far in a turn using the god power of the player.
+    Power(Vec<Position>),
 }
 
 // #[derive(Debug, Clone)]
@@ -16,7 +20,7 @@ pub enum PartialTurn {
 //     pub build: Option<Position>,
 // }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Turn {
     Setup(Position, Position),
     MoveBuild {
@@ -28,4 +32,222 @@ pub enum Turn {
         start: Position,
         end: Position,
     },
+    /// Artemis moves twice. `build` is `None` when the second move wins.
+    DoubleMove {
+        start: Position,
+        middle: Position,
+        end: Position,
+        build: Option<Position>,
+    },
+    /// Demeter builds on two different spaces, Hephaestus twice on the same one.
+    DoubleBuild {
+        start: Position,
+        end: Position,
+        build: Position,
+        second_build: Position,
+    },
+    /// Atlas builds a dome regardless of the level.
+    DomeBuild {
+        start: Position,
+        end: Position,
+        build: Position,
+    },
+    /// Prometheus builds before moving, without moving up.
+    BuildMoveBuild {
+        first_build: Position,
+        start: Position,
+        end: Position,
+        build: Position,
+    },
+}
+
+impl Turn {
+    /// Whether the turn wins the game for the player making it.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Turn::FinalMove { .. } | Turn::DoubleMove { build: None, .. }
+        )
+    }
+
+    /// Positions a human picks, in order, to make a turn using a god power:
+    /// the worker first, then every step. `None` for turns of the base rules.
+    pub fn power_selections(&self) -> Option<Vec<Position>> {
+        match *self {
+            Turn::DoubleMove {
+                start,
+                middle,
+                end,
+                build,
+            } => Some([start, middle, end].into_iter().chain(build).collect()),
+            Turn::DoubleBuild {
+                start,
+                end,
+                build,
+                second_build,
+            } => Some(vec![start, end, build, second_build]),
+            Turn::DomeBuild { start, end, build } => Some(vec![start, end, build]),
+            Turn::BuildMoveBuild {
+                first_build,
+                start,
+                end,
+                build,
+            } => Some(vec![start, first_build, end, build]),
+            Turn::Setup(..) | Turn::MoveBuild { .. } | Turn::FinalMove { .. } => None,
+        }
+    }
+}
+
+// Notation: `S c3 d3` places workers, `c3-d4^e5` moves and builds, `c3-d4#` wins.
+// God powers chain the same steps: `c3-d4-e5^e4` (Artemis), `c3-d4^e5^e4`
+// (Demeter, Hephaestus), `c3-d4*e5` for a dome (Atlas) and `b2^c3-d4^e5`
+// (Prometheus).
+impl Display for Turn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Turn::Setup(p1, p2) => write!(f, "S {p1} {p2}"),
+            Turn::MoveBuild { start, end, build } => write!(f, "{start}-{end}^{build}"),
+            Turn::FinalMove { start, end } => write!(f, "{start}-{end}#"),
+            Turn::DoubleMove {
+                start,
+                middle,
+                end,
+                build: Some(build),
+            } => write!(f, "{start}-{middle}-{end}^{build}"),
+            Turn::DoubleMove {
+                start,
+                middle,
+                end,
+                build: None,
+            } => write!(f, "{start}-{middle}-{end}#"),
+            Turn::DoubleBuild {
+                start,
+                end,
+                build,
+                second_build,
+            } => write!(f, "{start}-{end}^{build}^{second_build}"),
+            Turn::DomeBuild { start, end, build } => write!(f, "{start}-{end}*{build}"),
+            Turn::BuildMoveBuild {
+                first_build,
+                start,
+                end,
+                build,
+            } => write!(f, "{first_build}^{start}-{end}^{build}"),
+        }
+    }
+}
+
+impl FromStr for Turn {
+    type Err = error::NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || error::NotationError::InvalidTurn(s.to_string());
+        if let Some(setup) = s.strip_prefix("S ") {
+            let (p1, p2) = setup.trim().split_once(' ').ok_or_else(invalid)?;
+            return Ok(Turn::Setup(p1.parse()?, p2.trim().parse()?));
+        }
+        let (steps, is_final) = match s.strip_suffix('#') {
+            Some(steps) => (steps, true),
+            None => (s, false),
+        };
+        // Splits `c3-d4^e5` into `[(' ', c3), ('-', d4), ('^', e5)]`.
+        let mut parsed = vec![];
+        let mut rest = steps;
+        let mut separator = ' ';
+        loop {
+            let next = rest.find(['-', '^', '*']).unwrap_or(rest.len());
+            parsed.push((separator, rest[..next].parse::<Position>()?));
+            let Some(c) = rest[next..].chars().next() else {
+                break;
+            };
+            separator = c;
+            rest = &rest[next + 1..];
+        }
+        let turn = match (parsed.as_slice(), is_final) {
+            ([(' ', start), ('-', end), ('^', build)], false) => Turn::MoveBuild {
+                start: *start,
+                end: *end,
+                build: *build,
+            },
+            ([(' ', start), ('-', end)], true) => Turn::FinalMove {
+                start: *start,
+                end: *end,
+            },
+            ([(' ', start), ('-', middle), ('-', end), ('^', build)], false) => Turn::DoubleMove {
+                start: *start,
+                middle: *middle,
+                end: *end,
+                build: Some(*build),
+            },
+            ([(' ', start), ('-', middle), ('-', end)], true) => Turn::DoubleMove {
+                start: *start,
+                middle: *middle,
+                end: *end,
+                build: None,
+            },
+            ([(' ', start), ('-', end), ('^', build), ('^', second_build)], false) => {
+                Turn::DoubleBuild {
+                    start: *start,
+                    end: *end,
+                    build: *build,
+                    second_build: *second_build,
+                }
+            }
+            ([(' ', start), ('-', end), ('*', build)], false) => Turn::DomeBuild {
+                start: *start,
+                end: *end,
+                build: *build,
+            },
+            ([(' ', first_build), ('^', start), ('-', end), ('^', build)], false) => {
+                Turn::BuildMoveBuild {
+                    first_build: *first_build,
+                    start: *start,
+                    end: *end,
+                    build: *build,
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(turn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn notation() {
+        for notation in [
+            "S c3 d3",
+            "c3-d4^e5",
+            "c3-d4#",
+            "c3-d4-e5^e4",
+            "c3-d4-e5#",
+            "c3-d4^e5^e4",
+            "c3-d4*e5",
+            "b2^c3-d4^e5",
+        ] {
+            let turn: Turn = notation.parse().unwrap();
+            assert_eq!(turn.to_string(), notation);
+        }
+        assert!(matches!(
+            "a1-b2^c1".parse(),
+            Ok(Turn::MoveBuild { start, end, build })
+                if start == Position::new(0, 0)
+                    && end == Position::new(1, 1)
+                    && build == Position::new(0, 2)
+        ));
+        for invalid in [
+            "",
+            "S c3",
+            "c3",
+            "c3-d4",
+            "c3^d4",
+            "c3-d4^e5#",
+            "c3-d4-h5^e4",
+        ] {
+            assert!(invalid.parse::<Turn>().is_err(), "{invalid}");
+        }
+    }
 }
@@ -0,0 +1,67 @@
+use super::{board::Board, god::God, prelude::*};
+
+// Stateless splitmix64, so that keys can be computed at compile time.
+const fn key(index: usize) -> u64 {
+    let mut z = (index as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn keys<const N: usize>(offset: usize) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut i = 0;
+    while i < N {
+        keys[i] = key(offset + i);
+        i += 1;
+    }
+    keys
+}
+
+// Indexed by bit index times 4 plus level minus one, ground level has no key.
+const LEVELS: [u64; 256] = keys(0);
+const WORKERS: [u64; 256] = keys(256);
+const GODS: [u64; 36] = keys(512);
+// The first player to move has no key, so that an empty board hashes to zero.
+const TO_MOVE: [u64; 4] = [0, key(548), key(549), key(550)];
+pub const ATHENA_MOVED_UP: u64 = key(551);
+
+pub fn level(position: Position, construction: Construction) -> u64 {
+    match construction {
+        Construction::GroundLevel => 0,
+        construction => LEVELS[position.index() * 4 + construction as usize - 1],
+    }
+}
+
+pub fn worker(position: Position, player: Player) -> u64 {
+    WORKERS[player as usize * 64 + position.index()]
+}
+
+pub fn to_move(player: Player) -> u64 {
+    TO_MOVE[player as usize]
+}
+
+pub fn god(player: Player, god: God) -> u64 {
+    GODS[player as usize * 9 + god as usize]
+}
+
+/// Hash of `board` computed from scratch, which `Board` otherwise updates incrementally.
+pub fn hash(board: &Board) -> u64 {
+    let mut hash = 0;
+    for (position, tile) in board.get_tiles() {
+        hash ^= level(position, tile.construction);
+        if let Some(player) = tile.player {
+            hash ^= worker(position, player);
+        }
+    }
+    for player in board.players() {
+        if let Some(g) = board.get_player_god(player) {
+            hash ^= god(player, g);
+        }
+    }
+    hash ^= to_move(board.current_player());
+    if board.athena_moved_up {
+        hash ^= ATHENA_MOVED_UP;
+    }
+    hash
+}
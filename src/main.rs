@@ -1,60 +1,639 @@
 #![allow(dead_code)]
 #![feature(isolate_most_least_significant_one)]
 
-use control::{Message, handle_event};
-use game::prelude::Position;
+use std::time::{Duration, Instant};
+
+use control::{Message, TextInput, handle_event, poll_event, poll_key, poll_text};
+use game::{
+    clock::Clock,
+    prelude::{Position, Supply, WinReason},
+    record::Record,
+    save::Save,
+};
+use player::{
+    Decision, PLAYER_ID, PLAYER_TYPE, Player as _, PlayerOrHuman, analysis::Analysis,
+    get_player_from_spec, remote::RemotePlayer,
+};
 
 mod control;
 mod game;
 mod player;
 mod ui;
 
+const USAGE: &str = "usage: pompei [--record <file>] [--size <rows>x<cols>] [--limited-supply]
+                     [--players <2|3|4>] [--gods <god>,<god>...]
+                     [--host <port> | --join <address>]
+                     [--engine <command>] [--time <minutes>+<seconds> | --time <seconds>/move]
+                     [--save <file>] [--load <file>]
+       pompei --replay <file>
+       pompei perft <depth> [<fen>] [--divide]
+       pompei match <player> <opponent> [--games <n>] [--max-turns <n>]
+       pompei tournament <results-file> <player>... [--games <n>] [--max-turns <n>]
+       pompei engine <player>
+players: human, random, greedy, alphabeta[:<ms>], mcts[:<ms>], engine:<command>
+gods: one per player among apollo, artemis, athena, atlas, demeter, hephaestus,
+      minotaur, pan, prometheus, or - for none; p uses the power in a turn";
+
+// Longest time spent looking for a hint.
+const HINT_TIME: Duration = Duration::from_secs(5);
+
 fn main() {
-    let mut terminal = ratatui::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [flag, path] if flag == "--replay" => replay(path),
+        [command, options @ ..] if command == "perft" => perft(options),
+        [command, path, options @ ..] if command == "tournament" => tournament(path, options),
+        [command, id] if command == "engine" => engine(id),
+        [command, player, opponent, options @ ..] if command == "match" => {
+            run_match(player, opponent, options)
+        }
+        options => play(options),
+    }
+}
+
+fn play(options: &[String]) {
+    let mut record_path = None;
+    let (mut host, mut join) = (None, None);
+    let mut engine = None;
+    let mut time_control = None;
+    let (mut load, mut save_path) = (None, None);
+    let mut gods = None;
+    let mut board = game::board::Board::new();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let parsed = match option.as_str() {
+            "--record" => options.next().map(|path| record_path = Some(path)),
+            "--size" => options
+                .next()
+                .and_then(|size| size.parse().ok())
+                .map(|size| board.size = size),
+            "--players" => options
+                .next()
+                .and_then(|count| count.parse().ok())
+                .filter(|count| (2..=4).contains(count))
+                .map(|count| board.player_count = count),
+            "--gods" => options
+                .next()
+                .and_then(|list| {
+                    list.split(',')
+                        .map(|god| match god {
+                            "-" => Ok(None),
+                            god => god.parse().map(Some),
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                })
+                .map(|list| gods = Some(list)),
+            "--host" => options
+                .next()
+                .and_then(|port| port.parse::<u16>().ok())
+                .map(|port| host = Some(port)),
+            "--join" => options.next().map(|address| join = Some(address)),
+            "--engine" => options.next().map(|command| engine = Some(command)),
+            "--load" => options.next().map(|path| load = Some(path)),
+            "--save" => options.next().map(|path| save_path = Some(path)),
+            "--time" => options
+                .next()
+                .and_then(|control| control.parse().ok())
+                .map(|control| time_control = Some(control)),
+            "--limited-supply" => {
+                board.supply = Some(Supply::FULL);
+                Some(())
+            }
+            _ => None,
+        };
+        if parsed.is_none() {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+    if let Some(gods) = gods {
+        if gods.len() != board.player_count {
+            eprintln!("--gods needs one god per player\n{USAGE}");
+            std::process::exit(2);
+        }
+        for (player, god) in board.players().zip(gods) {
+            board.set_player_god(player, god);
+        }
+    }
+
+    // Each side would run its own clock, and flag the other on its own.
+    if (host.is_some() || join.is_some()) && (time_control.is_some() || load.is_some()) {
+        eprintln!("network games can neither be timed nor loaded\n{USAGE}");
+        std::process::exit(2);
+    }
+
+    // The opponent on the network, with the player they play.
+    let remote = match (host, join) {
+        (None, None) => None,
+        (Some(port), None) if board.player_count == 2 => {
+            println!("Waiting for an opponent on port {port}...");
+            let side = game::prelude::Player::Player2;
+            let remote = std::net::TcpListener::bind(("0.0.0.0", port))
+                .map_err(Into::into)
+                .and_then(|listener| RemotePlayer::host(&listener, &board, side));
+            Some(remote.map(|remote| (side, remote)))
+        }
+        (None, Some(address)) => Some(RemotePlayer::join(address).map(|(remote, side, joined)| {
+            board = joined;
+            // Network games have two players, the host playing the other side.
+            (game::prelude::Player::ALL[1 - side as usize], remote)
+        })),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let remote = remote.transpose().unwrap_or_else(|error| {
+        eprintln!("network error: {error}");
+        std::process::exit(1);
+    });
+
+    let save = load.map(|path| {
+        load_save(path).unwrap_or_else(|error| {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        })
+    });
+    // Saves go back to the loaded file by default.
+    let save_path = save_path.or(load).map_or("pompei.save", String::as_str);
+
+    let mut terminal = ui::init();
 
-    let (player1, player2) = ui::menu_widget::player_selection_menu(&mut terminal);
-    let mut game = game::Game::new();
+    let (mut game, mut clock, mut specs) = match save {
+        Some((game, clock, save)) => {
+            let specs = game
+                .board()
+                .players()
+                .map(|player| save.spec(player).unwrap_or("human").to_string())
+                .collect();
+            (game, clock, specs)
+        }
+        None => {
+            let local_players = board.player_count - usize::from(remote.is_some());
+            let mut choices = PLAYER_TYPE.to_vec();
+            if engine.is_some() {
+                choices.push("External engine");
+            }
+            let selections =
+                ui::menu_widget::player_selection_menu(&mut terminal, &choices, local_players);
+            let specs: Vec<String> = selections
+                .iter()
+                .map(|&selection| match engine {
+                    Some(command) if selection == PLAYER_TYPE.len() => format!("engine:{command}"),
+                    _ => PLAYER_ID[selection].to_string(),
+                })
+                .collect();
+            (
+                game::Game::from_board(board),
+                time_control.map(Clock::new),
+                specs,
+            )
+        }
+    };
+    let mut players: Vec<PlayerOrHuman> = specs
+        .iter()
+        .map(|spec| {
+            get_player_from_spec(spec).unwrap_or_else(|| {
+                ui::restore();
+                eprintln!("cannot start player {spec:?}");
+                std::process::exit(1);
+            })
+        })
+        .collect();
+    // The remote player is polled rather than waited for, so that the local
+    // side can chat, offer a draw or resign meanwhile.
+    let networked = remote.is_some();
+    if let Some((side, _)) = &remote {
+        specs.insert(*side as usize, "remote".to_string());
+        players.insert(*side as usize, None);
+    }
+    if let Some(clock) = &mut clock {
+        clock.switch(game.board().current_player(), Instant::now());
+    }
+    let mut saved = None;
+    let mut drawn = false;
+    let mut analysis: Option<Analysis> = None;
     let mut selected_tile = Position::new(0, 0);
-    let winner = loop {
+    let mut chat = vec![];
+    let mut chat_input: Option<String> = None;
+    let mut received = 0;
+    let outcome = loop {
+        let draw_offered = remote
+            .as_ref()
+            .is_some_and(|(_, remote)| remote.draw_offered());
         terminal
-            .draw(|frame| ui::draw(frame, &game, selected_tile))
+            .draw(|frame| {
+                let panel = ui::Chat {
+                    lines: &chat,
+                    input: chat_input.as_deref(),
+                    draw_offered,
+                };
+                ui::draw(
+                    frame,
+                    &game,
+                    selected_tile,
+                    clock.as_ref(),
+                    analysis.as_ref(),
+                    networked.then_some(&panel),
+                )
+            })
             .expect("failed to draw frame");
-        let current_player = match game.board().current_player() {
-            game::prelude::Player::Player1 => player1.as_ref(),
-            game::prelude::Player::Player2 => player2.as_ref(),
-        };
+        let current_player = current_player(&game, &players);
+        let (mover, played) = (game.board().current_player(), game.turns().count());
+        let remote_turn = remote.as_ref().is_some_and(|(side, _)| *side == mover);
+        let remote_decision = remote.as_ref().and_then(|(side, remote)| {
+            let decision = remote.poll();
+            let messages = remote.chat();
+            chat.extend(
+                messages[received..]
+                    .iter()
+                    .map(|text| format!("Opponent: {text}")),
+            );
+            received = messages.len();
+            decision.map(|decision| (*side, decision))
+        });
 
-        if let Some(player) = current_player {
-            let turn = player.play(game.board());
-            game.play(turn);
-        } else {
-            let Some(message) = handle_event() else {
-                continue;
-            };
+        if let Some((side, decision)) = remote_decision {
+            match decision {
+                Decision::Play(turn) => game.play(turn),
+                Decision::Resign => game.forfeit_by(side, WinReason::Resignation),
+                Decision::AgreeDraw => {
+                    drawn = true;
+                    break None;
+                }
+            }
+        } else if let Some(player) = current_player {
+            match player.play(game.board(), clock.as_ref()) {
+                _ if out_of_time(clock.as_ref()) => game.forfeit(WinReason::Time),
+                Decision::Play(turn) => game.play(turn),
+                Decision::Resign => game.forfeit(WinReason::Resignation),
+                Decision::AgreeDraw => {
+                    drawn = true;
+                    break None;
+                }
+            }
+        } else if out_of_time(clock.as_ref()) {
+            game.forfeit(WinReason::Time);
+        } else if analysis.as_mut().is_some_and(|analysis| !analysis.poll()) {
+            // Any key cancels the analysis.
+            if poll_key(Duration::from_millis(100)) {
+                analysis = None;
+            }
+        } else if let Some(input) = &mut chat_input {
+            match poll_text(Duration::from_millis(100)) {
+                Some(TextInput::Char(c)) => input.push(c),
+                Some(TextInput::Backspace) => {
+                    input.pop();
+                }
+                Some(TextInput::Send) => {
+                    let text = std::mem::take(input);
+                    chat_input = None;
+                    if let Some((_, remote)) = &remote {
+                        chat.push(match remote.say(&text) {
+                            Ok(()) => format!("You: {text}"),
+                            Err(error) => format!("Failed to send {text:?}: {error}"),
+                        });
+                    }
+                }
+                Some(TextInput::Cancel) => chat_input = None,
+                None => {}
+            }
+        } else if let Some(message) = poll_event(Duration::from_millis(100)) {
             match message {
+                // Only the turn of the other side is left to wait for.
+                Message::Select | Message::Click { .. } | Message::Reset | Message::Power
+                    if remote_turn => {}
                 Message::Select => select(&mut game, selected_tile),
-                Message::Up => selected_tile.up(),
-                Message::Down => selected_tile.down(),
-                Message::Left => selected_tile.left(),
-                Message::Right => selected_tile.right(),
+                Message::Click { column, row } => {
+                    let area = terminal.get_frame().area();
+                    if let Some(position) =
+                        ui::position_at(area, &game, clock.is_some(), networked, column, row)
+                    {
+                        selected_tile = position;
+                        select(&mut game, selected_tile);
+                    }
+                }
+                Message::Up => selected_tile.up(game.board().size),
+                Message::Down => selected_tile.down(game.board().size),
+                Message::Left => selected_tile.left(game.board().size),
+                Message::Right => selected_tile.right(game.board().size),
                 Message::Quit => break None,
                 Message::Reset => game.cancel(),
+                Message::Hint => analysis = Some(Analysis::start(game.board(), HINT_TIME)),
+                Message::Power => game.toggle_power(),
+                Message::Resign => match &remote {
+                    Some((side, _)) => {
+                        let local = game::prelude::Player::ALL[1 - *side as usize];
+                        game.forfeit_by(local, WinReason::Resignation);
+                    }
+                    None => game.forfeit(WinReason::Resignation),
+                },
+                Message::Draw => {
+                    if let Some((_, remote)) = &remote {
+                        let accepted = remote.draw_offered();
+                        chat.push(match remote.offer_draw() {
+                            Ok(()) if accepted => "You accepted the draw".to_string(),
+                            Ok(()) => "You offered a draw".to_string(),
+                            Err(error) => format!("Failed to offer a draw: {error}"),
+                        });
+                    }
+                }
+                Message::Chat if networked => chat_input = Some(String::new()),
+                Message::Chat => {}
+                // Taking back turns would get out of sync with the opponent.
+                Message::Undo | Message::Redo | Message::Save if networked => {}
+                Message::Save => {
+                    let save = Save::new(&game, &specs, clock.as_ref());
+                    saved = Some(std::fs::write(save_path, save.to_string()));
+                }
+                // Step back or forward to the next turn of a human player.
+                Message::Undo => while game.undo() && is_ai(&game, &players) {},
+                Message::Redo => while game.redo() && is_ai(&game, &players) {},
             };
         }
-        if let Some(winner) = game.winner() {
-            break Some(winner);
+        if game.turns().count() == played + 1 {
+            let (board, turn) = game.last_turn().unwrap();
+            for player in players.iter().flatten() {
+                player.turn_played(board, turn);
+            }
+            if let Some((_, remote)) = &remote {
+                remote.turn_played(board, turn);
+            }
+        }
+        if game.board().current_player() != mover || game.turns().count() != played {
+            // Hints are about the previous board.
+            analysis = None;
+            if let Some(clock) = &mut clock {
+                clock.switch(game.board().current_player(), Instant::now());
+            }
+        }
+        if let Some(outcome) = game.outcome() {
+            break Some(outcome);
         }
     };
-    ratatui::restore();
-    match winner {
-        Some(winner) => println!("{winner} won!"),
+    for player in players.iter().flatten() {
+        player.game_over(game.board());
+    }
+    if let Some((_, remote)) = &remote {
+        remote.game_over(game.board());
+    }
+    ui::restore();
+    match saved {
+        Some(Ok(())) => println!("Game saved to {save_path}"),
+        Some(Err(error)) => println!("Failed to save the game to {save_path}: {error}"),
+        None => {}
+    }
+    match outcome {
+        Some(outcome) => println!("{outcome}!"),
+        None if drawn => println!("Draw agreed"),
         None => println!("Game interrupted"),
     }
+    for (spec, player) in specs.iter().zip(&players) {
+        if let Some(error) = player.as_ref().and_then(|player| player.error()) {
+            println!("{}: {error}", player_name(spec));
+        }
+    }
+    if let Some(error) = remote.as_ref().and_then(|(_, remote)| remote.error()) {
+        println!("Remote player: {error}");
+    }
+    if let Some(path) = record_path {
+        let mut record = Record::from_game(&game);
+        for (index, spec) in specs.iter().enumerate() {
+            record.set_header(&format!("Player{}", index + 1), player_name(spec));
+        }
+        if let Some(control) = time_control {
+            record.set_header("TimeControl", &control.to_string());
+        }
+        record.set_result(outcome.map(|outcome| outcome.winner));
+        std::fs::write(path, record.to_string()).expect("failed to write the game record");
+    }
+}
+
+fn perft(options: &[String]) {
+    let divide = options.iter().any(|option| option == "--divide");
+    let options: Vec<&String> = options
+        .iter()
+        .filter(|option| *option != "--divide")
+        .collect();
+    let (depth, fen) = match options.as_slice() {
+        [depth] => (depth.parse::<u32>(), None),
+        [depth, fen] => (depth.parse::<u32>(), Some(fen.as_str())),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let Ok(depth) = depth else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    let board = match fen.map(game::board::Board::from_fen) {
+        None => game::board::Board::new(),
+        Some(Ok(board)) => board,
+        Some(Err(error)) => {
+            eprintln!("invalid position: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    let start = std::time::Instant::now();
+    let total = if divide {
+        let divided = game::perft::divide(&board, depth);
+        for (turn, count) in &divided {
+            println!("{turn}: {count}");
+        }
+        divided.iter().map(|(_, count)| count).sum()
+    } else {
+        game::perft::perft(&board, depth)
+    };
+    println!("{total} positions in {:.3?}", start.elapsed());
+}
+
+fn run_match(player_spec: &str, opponent_spec: &str, options: &[String]) {
+    let (mut games, mut max_turns) = (100, 200);
+    for option in options.chunks(2) {
+        let parsed = match option {
+            [flag, value] if flag == "--games" => value.parse().map(|value| games = value).is_ok(),
+            [flag, value] if flag == "--max-turns" => {
+                value.parse().map(|value| max_turns = value).is_ok()
+            }
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+    let (Some(player), Some(opponent)) = (ai_from_spec(player_spec), ai_from_spec(opponent_spec))
+    else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+
+    let stats = player::arena::play_match(player.as_ref(), opponent.as_ref(), games, max_turns);
+    println!(
+        "{} games: {} wins, {} losses, {} draws",
+        stats.games(),
+        stats.wins,
+        stats.losses,
+        stats.draws
+    );
+    println!("average length: {:.1} turns", stats.average_length());
+    println!(
+        "score: {:.3} ± {:.3} (95% confidence)",
+        stats.score(),
+        stats.confidence_interval()
+    );
+    for (spec, player) in [(player_spec, &player), (opponent_spec, &opponent)] {
+        if let Some(error) = player.error() {
+            println!("{spec}: {error}");
+        }
+    }
+}
+
+fn tournament(path: &str, options: &[String]) {
+    let (mut games, mut max_turns) = (10, 200);
+    let mut entrants = vec![];
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let parsed = match option.as_str() {
+            "--games" => options
+                .next()
+                .and_then(|value| value.parse().ok())
+                .map(|value| games = value),
+            "--max-turns" => options
+                .next()
+                .and_then(|value| value.parse().ok())
+                .map(|value| max_turns = value),
+            spec => ai_from_spec(spec).map(|player| {
+                entrants.push(player::tournament::Entrant {
+                    // Names are written to the results file without spaces.
+                    name: spec.split_whitespace().collect::<Vec<_>>().join("_"),
+                    player,
+                })
+            }),
+        };
+        if parsed.is_none() {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
+    let mut results = match std::fs::read_to_string(path) {
+        Ok(text) => text.parse().unwrap_or_else(|error| {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Default::default(),
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }
+    };
+    player::tournament::round_robin(&entrants, games, max_turns, &mut results, |results| {
+        std::fs::write(path, results.to_string()).expect("failed to write the results");
+    });
+    println!("{}", results.crosstable());
+    for entrant in &entrants {
+        if let Some(error) = entrant.player.error() {
+            println!("{}: {error}", entrant.name);
+        }
+    }
+}
+
+fn engine(id: &str) {
+    if id.contains(':') || ai_from_spec(id).is_none() {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    }
+    player::engine::run(id, std::io::stdin().lock(), std::io::stdout().lock())
+        .expect("failed to talk to the engine host");
+}
+
+fn load_save(path: &str) -> Result<(game::Game, Option<Clock>, Save), Box<dyn std::error::Error>> {
+    let save: Save = std::fs::read_to_string(path)?.parse()?;
+    Ok((save.game()?, save.clock()?, save))
+}
+
+// Display name of the player with the command line `spec`.
+fn player_name(spec: &str) -> &str {
+    let id = spec.split(':').next().unwrap_or(spec);
+    PLAYER_ID
+        .iter()
+        .position(|&name| name == id)
+        .map_or(spec, |index| PLAYER_TYPE[index])
+}
+
+fn ai_from_spec(spec: &str) -> Option<Box<dyn player::Player>> {
+    player::get_player_from_spec(spec).flatten()
+}
+
+fn replay(path: &str) {
+    let text = std::fs::read_to_string(path).expect("failed to read the game record");
+    let (record, mut game) = match text
+        .parse::<Record>()
+        .and_then(|record| Ok((record.clone(), record.replay()?)))
+    {
+        Ok(replay) => replay,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }
+    };
+    while game.undo() {}
+
+    let mut terminal = ui::init();
+    loop {
+        let played = game.turns().count();
+        let title = match record.turns.get(played) {
+            Some(next) => format!(
+                "Replay: turn {}/{}, next {next}",
+                played + 1,
+                record.turns.len()
+            ),
+            None => format!(
+                "Replay: {} turns, result {}",
+                played,
+                record.header("Result").unwrap_or("*")
+            ),
+        };
+        terminal
+            .draw(|frame| ui::draw_replay(frame, &game, &title))
+            .expect("failed to draw frame");
+        match handle_event() {
+            Some(Message::Right | Message::Select) => {
+                game.redo();
+            }
+            Some(Message::Left) => {
+                game.undo();
+            }
+            Some(Message::Quit) => break,
+            _ => {}
+        }
+    }
+    ui::restore();
+}
+
+fn out_of_time(clock: Option<&Clock>) -> bool {
+    clock.is_some_and(|clock| clock.flagged(Instant::now()).is_some())
 }
 
 fn select(game: &mut game::Game, position: Position) {
     if !game.selectable().contains(position) {
+        game.reject(position);
         return;
     }
     game.register_selection(position);
 }
+
+fn current_player<'a>(
+    game: &game::Game,
+    players: &'a [PlayerOrHuman],
+) -> Option<&'a dyn player::Player> {
+    players[game.board().current_player() as usize].as_deref()
+}
+
+fn is_ai(game: &game::Game, players: &[PlayerOrHuman]) -> bool {
+    current_player(game, players).is_some()
+}
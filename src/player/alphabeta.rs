@@ -0,0 +1,357 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use super::{
+    Decision, Player, greedy,
+    transposition::{Bound, SharedTable, TranspositionTable},
+};
+use crate::game::{
+    board::Board,
+    clock::Clock,
+    movegen::{Move, MoveList},
+    prelude,
+    turn::Turn,
+};
+
+// Score of a won position, reduced by the number of plies needed to reach it
+// so that faster wins are preferred.
+const WIN: i64 = 1_000_000;
+const MAX_PLY: i64 = 1_000;
+
+/// Negamax search with alpha-beta pruning and iterative deepening.
+///
+/// With more than two players the search is paranoid: every opponent of the
+/// player searching for a turn is assumed to play against them, so the game
+/// is searched as one between two sides. `eval` scores a board from the point
+/// of view of the given player.
+pub struct AlphaBeta<Eval> {
+    pub eval: Eval,
+    pub max_depth: u32,
+    pub time_budget: Duration,
+    pub table: SharedTable,
+}
+
+struct SearchState<'a> {
+    // The player the turn is searched for.
+    root: prelude::Player,
+    deadline: Instant,
+    // Stops the search early when set.
+    cancel: Option<&'a AtomicBool>,
+    table: &'a mut TranspositionTable,
+}
+
+impl SearchState<'_> {
+    // Whether `player` plays on the side of the root player.
+    fn with_root(&self, board: &Board, player: prelude::Player) -> bool {
+        board.same_side(self.root, player)
+    }
+
+    // Turns `score`, from the point of view of the root player, into one for
+    // the player to move.
+    fn for_mover(&self, board: &Board, score: i64) -> i64 {
+        if self.with_root(board, board.current_player()) {
+            score
+        } else {
+            -score
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        Instant::now() >= self.deadline
+            || self
+                .cancel
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+impl<Eval: Fn(&Board, prelude::Player) -> i64> Player for AlphaBeta<Eval> {
+    fn play(&self, board: &Board, clock: Option<&Clock>) -> Decision {
+        let mut table = self.table.lock().unwrap();
+        table.new_search();
+        let mut state = SearchState {
+            root: board.current_player(),
+            deadline: Instant::now()
+                + clock.map_or(self.time_budget, |clock| {
+                    clock.budget(board.current_player(), Instant::now())
+                }),
+            cancel: None,
+            table: &mut table,
+        };
+        let (moves, _) = self.deepen(board, &mut state);
+        Decision::Play(board.turn(moves[0]))
+    }
+}
+
+impl<Eval: Fn(&Board, prelude::Player) -> i64> AlphaBeta<Eval> {
+    /// Best turn for the player to move with its score, searching until the
+    /// time budget runs out or `cancel` is set. `None` when not even the first
+    /// iteration completed.
+    pub fn analyse(&self, board: &Board, cancel: &AtomicBool) -> Option<(Turn, i64)> {
+        let mut table = self.table.lock().unwrap();
+        table.new_search();
+        let mut state = SearchState {
+            root: board.current_player(),
+            deadline: Instant::now() + self.time_budget,
+            cancel: Some(cancel),
+            table: &mut table,
+        };
+        let (moves, score) = self.deepen(board, &mut state);
+        Some((board.turn(*moves.first()?), score?))
+    }
+
+    // Iterative deepening, returning the moves best first and the score of the
+    // deepest completed iteration.
+    fn deepen(&self, board: &Board, state: &mut SearchState) -> (MoveList, Option<i64>) {
+        let mut moves: MoveList = board.moves().collect();
+        let mut result = None;
+        for depth in 1..=self.max_depth {
+            let Some((best, score)) = self.root(board, &moves, depth, state) else {
+                break;
+            };
+            // Search the best move first on the next iteration.
+            moves.swap(0, best);
+            result = Some(score);
+            if score.abs() >= WIN - MAX_PLY {
+                break;
+            }
+        }
+        (moves, result)
+    }
+
+    fn root(
+        &self,
+        board: &Board,
+        moves: &[Move],
+        depth: u32,
+        state: &mut SearchState,
+    ) -> Option<(usize, i64)> {
+        let mut best = (0, -WIN);
+        let mut alpha = -WIN;
+        let mut board = board.clone();
+        for (index, &mv) in moves.iter().enumerate() {
+            let score = if mv.is_final() {
+                WIN - 1
+            } else {
+                let undo = board.apply(&board.turn(mv)).unwrap();
+                let score = self.child(&mut board, depth, 0, alpha, WIN, state);
+                board.undo(undo);
+                score?
+            };
+            if score > best.1 {
+                best = (index, score);
+            }
+            alpha = alpha.max(score);
+        }
+        Some(best)
+    }
+
+    fn search(
+        &self,
+        board: &mut Board,
+        depth: u32,
+        ply: i64,
+        mut alpha: i64,
+        beta: i64,
+        state: &mut SearchState,
+    ) -> Option<i64> {
+        if state.stopped() {
+            return None;
+        }
+        // Eliminations can end the game, or leave the root's side out of it.
+        if let Some(outcome) = board.won {
+            let score = if state.with_root(board, outcome.winner) {
+                WIN - ply
+            } else {
+                -(WIN - ply)
+            };
+            return Some(state.for_mover(board, score));
+        }
+        if board
+            .players()
+            .filter(|&player| state.with_root(board, player))
+            .all(|player| board.eliminated[player as usize])
+        {
+            return Some(state.for_mover(board, -(WIN - ply)));
+        }
+        if board.moves().next().is_none() {
+            return Some(-(WIN - ply));
+        }
+        if board.has_winning_turn() {
+            return Some(WIN - ply - 1);
+        }
+        if depth == 0 {
+            return Some(state.for_mover(board, (self.eval)(board, state.root)));
+        }
+
+        let mut first = None;
+        if let Some(entry) = state.table.probe(board.hash) {
+            let score = from_table(entry.score, ply);
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return Some(score),
+                    Bound::Lower if score >= beta => return Some(score),
+                    Bound::Upper if score <= alpha => return Some(score),
+                    _ => {}
+                }
+            }
+            first = entry.best_move.clone();
+        }
+
+        // The best move of the table goes first.
+        let moves: MoveList = board.moves().collect();
+        let workers = board.get_player_meeple(board.current_player());
+        let generated = moves
+            .iter()
+            .map(|mv| mv.decode(workers).unwrap())
+            .filter(|turn| Some(turn) != first.as_ref());
+        let original_alpha = alpha;
+        let mut best = None;
+        for turn in first.clone().into_iter().chain(generated) {
+            // Only the move of the table can be illegal, after a hash collision.
+            let Ok(undo) = board.apply(&turn) else {
+                continue;
+            };
+            let score = self.child(board, depth, ply, alpha, beta, state);
+            board.undo(undo);
+            let score = score?;
+            if score > alpha {
+                alpha = score;
+                best = Some(turn);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        let bound = if alpha >= beta {
+            Bound::Lower
+        } else if alpha > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        state
+            .table
+            .store(board.hash, depth, to_table(alpha, ply), bound, best);
+        Some(alpha)
+    }
+
+    // Searches the board reached by a turn played at `ply`, scoring it for the
+    // player who played it. Scores only change sign when the turn passes to the
+    // other side.
+    fn child(
+        &self,
+        board: &mut Board,
+        depth: u32,
+        ply: i64,
+        alpha: i64,
+        beta: i64,
+        state: &mut SearchState,
+    ) -> Option<i64> {
+        let mover = board.previous_player();
+        if state.with_root(board, mover) == state.with_root(board, board.current_player()) {
+            self.search(board, depth - 1, ply + 1, alpha, beta, state)
+        } else {
+            Some(-self.search(board, depth - 1, ply + 1, -beta, -alpha, state)?)
+        }
+    }
+}
+
+// Win scores depend on the distance to the root, the table stores the distance
+// from the stored position instead.
+fn to_table(score: i64, ply: i64) -> i64 {
+    if score >= WIN - MAX_PLY {
+        score + ply
+    } else if score <= -(WIN - MAX_PLY) {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i64, ply: i64) -> i64 {
+    if score >= WIN - MAX_PLY {
+        score - ply
+    } else if score <= -(WIN - MAX_PLY) {
+        score + ply
+    } else {
+        score
+    }
+}
+
+/// Describes a score of `AlphaBeta::analyse`, as `+3` or `win in 2`.
+pub fn format_score(score: i64) -> String {
+    if score >= WIN - MAX_PLY {
+        format!("win in {}", WIN - score)
+    } else if score <= -(WIN - MAX_PLY) {
+        format!("loss in {}", WIN + score)
+    } else {
+        format!("{score:+}")
+    }
+}
+
+/// Elevation of the side of `player` minus that of its opponents.
+pub fn elevation_difference(board: &Board, player: prelude::Player) -> i64 {
+    board
+        .players()
+        .map(|other| {
+            let elevation = greedy::player_elevation(board, other);
+            if board.same_side(player, other) {
+                elevation
+            } else {
+                -elevation
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::prelude::Position;
+
+    fn search_player() -> AlphaBeta<fn(&Board, prelude::Player) -> i64> {
+        AlphaBeta {
+            eval: elevation_difference,
+            max_depth: 3,
+            time_budget: Duration::from_secs(10),
+            table: TranspositionTable::shared(1 << 12),
+        }
+    }
+
+    #[test]
+    fn takes_immediate_win() {
+        let board = Board::from_fen("00000b/02a000/00300/00000/0b0000a a").unwrap();
+
+        let turn = search_player().play(&board, None);
+        assert!(matches!(
+            turn,
+            Decision::Play(Turn::FinalMove { start, end })
+                if start == Position::new(1, 1) && end == Position::new(2, 2)
+        ));
+    }
+
+    #[test]
+    fn blocks_opponent_win() {
+        let board = Board::from_fen("00000b/00000/002b00/03000/0a0a000 a").unwrap();
+
+        let turn = search_player().play(&board, None);
+        assert!(matches!(
+            turn,
+            Decision::Play(Turn::MoveBuild { build, .. }) if build == Position::new(3, 1)
+        ));
+    }
+
+    #[test]
+    fn blocks_third_player_win() {
+        // Player 3 climbs to c4 unless player 1 domes it first.
+        let board = Board::from_fen("0c000a0/00000/002c30/0b0000/0b0000a a -/-/-").unwrap();
+
+        let turn = search_player().play(&board, None);
+        assert!(matches!(
+            turn,
+            Decision::Play(Turn::MoveBuild { build, .. }) if build == Position::new(2, 3)
+        ));
+    }
+}
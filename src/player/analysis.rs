@@ -0,0 +1,119 @@
+use std::{
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::{alphabeta, transposition::TranspositionTable};
+use crate::game::{board::Board, turn::Turn};
+
+/// The turn recommended by the analysis, with its score for the player to move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    pub turn: Turn,
+    pub score: i64,
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.turn, alphabeta::format_score(self.score))
+    }
+}
+
+/// An alpha-beta search of a board running in the background.
+///
+/// Dropping the analysis cancels the search.
+pub struct Analysis {
+    cancel: Arc<AtomicBool>,
+    search: Option<JoinHandle<Option<Hint>>>,
+    hint: Option<Hint>,
+}
+
+impl Analysis {
+    pub fn start(board: &Board, budget: Duration) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let search = {
+            let (board, cancel) = (board.clone(), cancel.clone());
+            thread::spawn(move || {
+                let search = alphabeta::AlphaBeta {
+                    eval: alphabeta::elevation_difference,
+                    max_depth: 64,
+                    time_budget: budget,
+                    table: TranspositionTable::shared(1 << 18),
+                };
+                search
+                    .analyse(&board, &cancel)
+                    .map(|(turn, score)| Hint { turn, score })
+            })
+        };
+        Self {
+            cancel,
+            search: Some(search),
+            hint: None,
+        }
+    }
+
+    /// Collects the result once the search is over, returning whether it is.
+    pub fn poll(&mut self) -> bool {
+        if self.search.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.hint = self
+                .search
+                .take()
+                .and_then(|search| search.join().ok().flatten());
+        }
+        self.search.is_none()
+    }
+
+    pub fn running(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// The recommended turn, once the search is over and if it found one.
+    pub fn hint(&self) -> Option<&Hint> {
+        self.hint.as_ref()
+    }
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::prelude::Position;
+
+    #[test]
+    fn finds_win_in_background() {
+        let board = Board::from_fen("00000b/02a000/00300/00000/0b0000a a").unwrap();
+        let mut analysis = Analysis::start(&board, Duration::from_secs(10));
+        while !analysis.poll() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!analysis.running());
+        let hint = analysis.hint().unwrap();
+        assert_eq!(
+            hint.turn,
+            Turn::FinalMove {
+                start: Position::new(1, 1),
+                end: Position::new(2, 2)
+            }
+        );
+        assert_eq!(hint.to_string(), "b2-c3# (win in 1)");
+    }
+
+    #[test]
+    fn cancelled_on_drop() {
+        let mut analysis = Analysis::start(&Board::new(), Duration::from_secs(600));
+        let search = analysis.search.take().unwrap();
+        drop(analysis);
+        // The search stops long before its budget.
+        assert!(search.join().is_ok());
+    }
+}
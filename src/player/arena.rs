@@ -0,0 +1,147 @@
+use super::{Decision, Player};
+use crate::game::{board::Board, prelude};
+
+/// How a game between two AI players ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    /// `None` when the game reached the turn limit.
+    pub winner: Option<prelude::Player>,
+    pub turns: usize,
+}
+
+/// Plays a full game without a terminal, `player1` placing its workers first.
+///
+/// Games longer than `max_turns` are stopped and counted as draws.
+pub fn play_game(player1: &dyn Player, player2: &dyn Player, max_turns: usize) -> GameResult {
+    let mut board = Board::new();
+    let mut turns = 0;
+    let outcome = loop {
+        let outcome = board.outcome();
+        if outcome.is_some() || turns == max_turns {
+            break outcome;
+        }
+        let player = [player1, player2][board.current_player() as usize];
+        board = match player.play(&board, None) {
+            Decision::Play(turn) => {
+                player1.turn_played(&board, &turn);
+                player2.turn_played(&board, &turn);
+                board.action(&turn).unwrap()
+            }
+            Decision::Resign => board.resign(),
+            Decision::AgreeDraw => break None,
+        };
+        turns += 1;
+    };
+    player1.game_over(&board);
+    player2.game_over(&board);
+    GameResult {
+        winner: outcome.map(|outcome| outcome.winner),
+        turns,
+    }
+}
+
+/// Results of a match, from the point of view of its first player.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub total_turns: usize,
+}
+
+impl Stats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn average_length(&self) -> f64 {
+        self.total_turns as f64 / f64::from(self.games().max(1))
+    }
+
+    /// Points per game, counting a draw as half a win.
+    pub fn score(&self) -> f64 {
+        (f64::from(self.wins) + f64::from(self.draws) / 2.) / f64::from(self.games().max(1))
+    }
+
+    /// Half-width of the 95% confidence interval on `score`, using the normal
+    /// approximation.
+    pub fn confidence_interval(&self) -> f64 {
+        let games = f64::from(self.games());
+        if games < 2. {
+            return 0.5;
+        }
+        let score = self.score();
+        let squares = f64::from(self.wins) + f64::from(self.draws) / 4.;
+        let variance = (squares / games - score * score) * games / (games - 1.);
+        1.96 * (variance / games).sqrt()
+    }
+
+    fn record(&mut self, result: GameResult, player: prelude::Player) {
+        self.total_turns += result.turns;
+        match result.winner {
+            Some(winner) if winner == player => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// Plays `games` games between `player` and `opponent`, alternating who
+/// places their workers first.
+pub fn play_match(
+    player: &dyn Player,
+    opponent: &dyn Player,
+    games: u32,
+    max_turns: usize,
+) -> Stats {
+    let mut stats = Stats::default();
+    for game in 0..games {
+        if game % 2 == 0 {
+            stats.record(
+                play_game(player, opponent, max_turns),
+                prelude::Player::Player1,
+            );
+        } else {
+            stats.record(
+                play_game(opponent, player, max_turns),
+                prelude::Player::Player2,
+            );
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::player::{greedy, random::RandomPlayer};
+
+    #[test]
+    fn match_between_players() {
+        let greedy = greedy::Greedy {
+            eval: greedy::elevation,
+        };
+        let stats = play_match(&greedy, &RandomPlayer, 10, 200);
+        assert_eq!(stats.games(), 10);
+        assert!(stats.average_length() >= 2.);
+
+        let stats = play_match(&RandomPlayer, &RandomPlayer, 2, 1);
+        assert_eq!(stats.draws, 2);
+        assert_eq!(stats.total_turns, 2);
+    }
+
+    #[test]
+    fn statistics() {
+        let stats = Stats {
+            wins: 6,
+            losses: 2,
+            draws: 2,
+            total_turns: 250,
+        };
+        assert_eq!(stats.score(), 0.7);
+        assert_eq!(stats.average_length(), 25.);
+        let interval = stats.confidence_interval();
+        assert!(0.25 < interval && interval < 0.3, "{interval}");
+        assert_eq!(Stats::default().confidence_interval(), 0.5);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{Decision, Player};
+use crate::game::{board::Board, clock::Clock, error::NotationError, turn::Turn};
+
+// Engine protocol, one command per line on the engine's standard input:
+//
+//   pompei                               answered by `id name <name>` lines, then `pompeiok`
+//   isready                              answered by `readyok`
+//   position <fen>|startpos [moves <turn>...]
+//   go [movetime <ms>]                   answered by `bestmove <turn>` or `bestmove resign`
+//   quit
+//
+// Turns use the record notation, except that worker placements are written
+// `c3,d3` so that turns contain no space. Engines may send `info ...` lines at
+// any time, which are ignored.
+
+fn write_turn(turn: &Turn) -> String {
+    match turn {
+        Turn::Setup(first, second) => format!("{first},{second}"),
+        turn => turn.to_string(),
+    }
+}
+
+fn parse_turn(s: &str) -> Result<Turn, NotationError> {
+    match s.split_once(',') {
+        Some((first, second)) => Ok(Turn::Setup(first.parse()?, second.parse()?)),
+        None => s.parse(),
+    }
+}
+
+// Longest wait for the engine to start, and for a move past its thinking time.
+const STARTUP_TIME: Duration = Duration::from_secs(10);
+const MOVE_MARGIN: Duration = Duration::from_secs(1);
+
+/// An engine running in another process, speaking the engine protocol.
+/// Illegal moves, late moves and failures of the engine count as a
+/// resignation, after which the engine is stopped.
+pub struct ExternalEngine {
+    process: Mutex<Process>,
+    movetime: Duration,
+}
+
+struct Process {
+    child: Child,
+    input: ChildStdin,
+    // Lines of the engine's output, read on another thread so that reads can
+    // time out.
+    output: Receiver<String>,
+    error: Option<String>,
+}
+
+impl Process {
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.input, "{command}")?;
+        self.input.flush()
+    }
+
+    // Reads lines until one starts with `prefix`, returning the rest of it. An
+    // engine not answering within `timeout` is stopped.
+    fn expect(&mut self, prefix: &str, timeout: Duration) -> io::Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.output.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // A late answer would be read as the answer to the next command.
+                    let _ = self.child.kill();
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("no {prefix} after {}ms", timeout.as_millis()),
+                    ));
+                }
+            };
+            if let Some(rest) = line.trim_end().strip_prefix(prefix) {
+                return Ok(rest.trim().to_string());
+            }
+        }
+    }
+
+    fn best_move(&mut self, board: &Board, movetime: Duration) -> Result<Decision, String> {
+        self.send(&format!("position {}", board.to_fen()))
+            .and_then(|()| self.send(&format!("go movetime {}", movetime.as_millis())))
+            .and_then(|()| self.expect("bestmove", movetime + MOVE_MARGIN))
+            .map_err(|error| error.to_string())
+            .and_then(|best| match best.as_str() {
+                "resign" => Ok(Decision::Resign),
+                best => {
+                    let turn = parse_turn(best).map_err(|error| error.to_string())?;
+                    board
+                        .action(&turn)
+                        .map_err(|error| format!("illegal move {best}: {error}"))?;
+                    Ok(Decision::Play(turn))
+                }
+            })
+    }
+}
+
+impl ExternalEngine {
+    /// Starts `command` and waits until the engine is ready. Every move is
+    /// given `movetime` to think.
+    pub fn spawn(command: &mut Command, movetime: Duration) -> io::Result<Self> {
+        // The engine's errors would be written over the terminal UI.
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (lines, output) = mpsc::channel();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut process = Process {
+            input: child.stdin.take().unwrap(),
+            output,
+            child,
+            error: None,
+        };
+        process.send("pompei")?;
+        process.expect("pompeiok", STARTUP_TIME)?;
+        process.send("isready")?;
+        process.expect("readyok", STARTUP_TIME)?;
+        Ok(Self {
+            process: Mutex::new(process),
+            movetime,
+        })
+    }
+
+    /// Starts the engine whose command line is `command`, split on whitespace.
+    pub fn from_command_line(command: &str, movetime: Duration) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or(io::ErrorKind::InvalidInput)?;
+        Self::spawn(Command::new(program).args(words), movetime)
+    }
+}
+
+impl Player for ExternalEngine {
+    fn play(&self, board: &Board, clock: Option<&Clock>) -> Decision {
+        let movetime = clock.map_or(self.movetime, |clock| {
+            clock.budget(board.current_player(), std::time::Instant::now())
+        });
+        let mut process = self.process.lock().unwrap();
+        process.best_move(board, movetime).unwrap_or_else(|error| {
+            // Later failures follow from the first one.
+            process.error.get_or_insert(error);
+            Decision::Resign
+        })
+    }
+
+    fn error(&self) -> Option<String> {
+        self.process.lock().unwrap().error.clone()
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        let process = self.process.get_mut().unwrap();
+        let _ = process.send("quit");
+        let _ = process.child.kill();
+        let _ = process.child.wait();
+    }
+}
+
+fn parse_position(arguments: &str) -> Result<Board, String> {
+    let (position, moves) = match arguments.split_once(" moves") {
+        Some((position, moves)) => (position, moves),
+        None => (arguments, ""),
+    };
+    let mut board = match position.trim() {
+        "startpos" => Board::new(),
+        fen => Board::from_fen(fen).map_err(|error| error.to_string())?,
+    };
+    for token in moves.split_whitespace() {
+        let turn = parse_turn(token).map_err(|error| error.to_string())?;
+        board = board
+            .action(&turn)
+            .map_err(|error| format!("illegal move {token}: {error}"))?;
+    }
+    Ok(board)
+}
+
+/// Runs the AI with the command line `id` as an engine reading commands from
+/// `input`, until `quit` or the end of the input.
+pub fn run(id: &str, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut board = Board::new();
+    for line in input.lines() {
+        let line = line?;
+        let (command, arguments) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        match command {
+            "pompei" => writeln!(output, "id name pompei {id}\npompeiok")?,
+            "isready" => writeln!(output, "readyok")?,
+            "position" => match parse_position(arguments) {
+                Ok(position) => board = position,
+                Err(error) => writeln!(output, "info string {error}")?,
+            },
+            "go" => {
+                let millis = match arguments.split_whitespace().collect::<Vec<_>>()[..] {
+                    ["movetime", millis] => millis.parse().unwrap_or(1000),
+                    _ => 1000,
+                };
+                let player = super::get_player_from_spec(&format!("{id}:{millis}")).flatten();
+                let best = match player {
+                    Some(player) if !board.possible_move().is_empty() => {
+                        match player.play(&board, None) {
+                            Decision::Play(turn) => write_turn(&turn),
+                            _ => "resign".to_string(),
+                        }
+                    }
+                    _ => "resign".to_string(),
+                };
+                writeln!(output, "bestmove {best}")?;
+            }
+            "quit" => break,
+            "" => {}
+            command => writeln!(output, "info string unknown command {command:?}")?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::prelude::Position;
+
+    #[test]
+    fn turn_tokens() {
+        let setup = Turn::Setup(Position::new(2, 2), Position::new(2, 3));
+        assert_eq!(write_turn(&setup), "c3,d3");
+        assert_eq!(parse_turn("c3,d3"), Ok(setup));
+        assert_eq!(parse_turn("c3-d4^e5").unwrap().to_string(), "c3-d4^e5");
+        assert!(parse_turn("c3 d3").is_err());
+    }
+
+    #[test]
+    fn engine_mode() {
+        let input = "pompei\nisready\nposition startpos moves c3,d3 b2,b4\ngo movetime 10\n\
+                     position startpos moves c3,c3\ngo\nquit\ngo\n";
+        let mut output = vec![];
+        run("greedy", input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[..3], ["id name pompei greedy", "pompeiok", "readyok"]);
+        let best = lines[3].strip_prefix("bestmove ").unwrap();
+        let board = parse_position("startpos moves c3,d3 b2,b4").unwrap();
+        assert!(board.action(&parse_turn(best).unwrap()).is_ok());
+        assert!(lines[4].starts_with("info string illegal move c3,c3"));
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn external_engine() {
+        // Always places its workers on c3 and d3.
+        let script = "while read command arguments; do case $command in
+            pompei) echo pompeiok;;
+            isready) echo readyok;;
+            go) echo info thinking; echo bestmove c3,d3;;
+            quit) exit;;
+            esac; done";
+        let engine = ExternalEngine::spawn(
+            Command::new("sh").args(["-c", script]),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+        assert_eq!(
+            engine.play(&Board::new(), None),
+            Decision::Play(Turn::Setup(Position::new(2, 2), Position::new(2, 3)))
+        );
+        assert_eq!(engine.error(), None);
+
+        let occupied = parse_position("startpos moves c3,d3").unwrap();
+        assert_eq!(engine.play(&occupied, None), Decision::Resign);
+        assert!(engine.error().unwrap().starts_with("illegal move c3,d3"));
+    }
+
+    #[test]
+    fn hanging_engine() {
+        // Never answers `go`, and writes to its standard error.
+        let script = "while read command arguments; do case $command in
+            pompei) echo pompeiok;;
+            isready) echo readyok;;
+            go) echo thinking >&2;;
+            esac; done";
+        let engine = ExternalEngine::spawn(
+            Command::new("sh").args(["-c", script]),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+        let start = Instant::now();
+        assert_eq!(engine.play(&Board::new(), None), Decision::Resign);
+        assert!(start.elapsed() < MOVE_MARGIN * 2);
+        assert!(engine.error().unwrap().starts_with("no bestmove"));
+        // The engine was stopped, so it does not answer later turns late.
+        assert_eq!(engine.play(&Board::new(), None), Decision::Resign);
+        assert!(engine.error().unwrap().starts_with("no bestmove"));
+    }
+}
@@ -1,25 +1,50 @@
-use super::Player;
+use super::{Decision, Player};
 
 pub struct Greedy<Eval> {
     pub eval: Eval,
 }
 
 impl<Eval: Fn(&crate::game::board::Board) -> i64> Player for Greedy<Eval> {
-    fn play(&self, board: &crate::game::board::Board) -> crate::game::turn::Turn {
+    fn play(
+        &self,
+        board: &crate::game::board::Board,
+        _clock: Option<&crate::game::clock::Clock>,
+    ) -> Decision {
         let possible_moves = board.possible_move();
+        let player = board.current_player();
+        let mut board = board.clone();
 
-        possible_moves
+        let turn = possible_moves
             .iter()
-            .max_by_key(|turn| (self.eval)(&board.action(turn).unwrap()))
+            .max_by_key(|turn| {
+                let undo = board.apply(turn).unwrap();
+                let score = match board.outcome() {
+                    Some(outcome) if board.same_side(outcome.winner, player) => i64::MAX,
+                    Some(_) => i64::MIN,
+                    None => (self.eval)(&board),
+                };
+                board.undo(undo);
+                score
+            })
             .unwrap()
-            .clone()
+            .clone();
+        Decision::Play(turn)
     }
 }
 
 pub fn elevation(board: &crate::game::board::Board) -> i64 {
-    let player = board.current_player().other_player();
+    player_elevation(board, board.previous_player())
+}
+
+pub fn player_elevation(
+    board: &crate::game::board::Board,
+    player: crate::game::prelude::Player,
+) -> i64 {
     let meeples = board.get_player_meeple(player);
-    (board.first_floor.intersection(meeples).len()
-        + board.second_floor.intersection(meeples).len() * 2
-        + board.third_floor.intersection(meeples).len() * 3) as i64
+    // Each plane a worker stands on adds a level.
+    board
+        .levels
+        .iter()
+        .map(|plane| plane.intersection(meeples).len() as i64)
+        .sum()
 }
@@ -0,0 +1,236 @@
+use std::time::{Duration, Instant};
+
+use rand::{SeedableRng, prelude::*, rngs::StdRng};
+
+use super::{Decision, Player};
+use crate::game::{board::Board, clock::Clock, movegen::MoveList, prelude, turn::Turn};
+
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+pub enum Rollout {
+    /// Uniformly random playouts, as played by `RandomPlayer`.
+    Random,
+    /// Plays the move maximizing `eval` (scored for the player who just moved,
+    /// like `greedy::elevation`), or a random one with probability `epsilon`.
+    Biased {
+        eval: fn(&Board) -> i64,
+        epsilon: f64,
+    },
+}
+
+/// Monte Carlo tree search using UCT to pick which branch to explore.
+pub struct Mcts {
+    pub budget: Budget,
+    pub exploration: f64,
+    pub rollout: Rollout,
+    /// Fixed seed for reproducible games, otherwise seeded from the OS.
+    pub seed: Option<u64>,
+}
+
+struct Node {
+    board: Board,
+    turn: Option<Turn>,
+    mover: prelude::Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Turn>,
+    winner: Option<prelude::Player>,
+    visits: u32,
+    wins: f64,
+}
+
+impl Node {
+    fn new(
+        board: Board,
+        turn: Option<Turn>,
+        mover: prelude::Player,
+        parent: Option<usize>,
+    ) -> Self {
+        let untried = board.possible_move();
+        let winner = match board.won {
+            Some(outcome) => Some(outcome.winner),
+            None if untried.is_empty() => Some(board.previous_player()),
+            None => None,
+        };
+        Self {
+            board,
+            turn,
+            mover,
+            parent,
+            children: vec![],
+            untried: if winner.is_some() { vec![] } else { untried },
+            winner,
+            visits: 0,
+            wins: 0.,
+        }
+    }
+}
+
+impl Player for Mcts {
+    fn play(&self, board: &Board, clock: Option<&Clock>) -> Decision {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        let start = Instant::now();
+        let mut tree = vec![Node::new(
+            board.clone(),
+            None,
+            board.previous_player(),
+            None,
+        )];
+        let mut iterations = 0;
+        let budget = match (clock, &self.budget) {
+            (Some(clock), _) => Budget::Time(clock.budget(board.current_player(), start)),
+            (None, Budget::Iterations(n)) => Budget::Iterations(*n),
+            (None, Budget::Time(budget)) => Budget::Time(*budget),
+        };
+        while match budget {
+            Budget::Iterations(n) => iterations < n,
+            Budget::Time(budget) => start.elapsed() < budget,
+        } {
+            self.iterate(&mut tree, &mut rng);
+            iterations += 1;
+        }
+        let best = tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| tree[child].visits)
+            .copied();
+        Decision::Play(match best {
+            Some(child) => tree[child].turn.clone().unwrap(),
+            None => tree[0].untried.choose(&mut rng).unwrap().clone(),
+        })
+    }
+}
+
+impl Mcts {
+    fn iterate(&self, tree: &mut Vec<Node>, rng: &mut StdRng) {
+        let mut node = 0;
+        while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+            node = self.select_child(tree, node);
+        }
+        if !tree[node].untried.is_empty() {
+            let index = rng.random_range(0..tree[node].untried.len());
+            let turn = tree[node].untried.swap_remove(index);
+            let parent = &tree[node];
+            let child = Node::new(
+                parent.board.action(&turn).unwrap(),
+                Some(turn),
+                parent.board.current_player(),
+                Some(node),
+            );
+            tree.push(child);
+            let child = tree.len() - 1;
+            tree[node].children.push(child);
+            node = child;
+        }
+        let winner = match tree[node].winner {
+            Some(winner) => winner,
+            None => self.simulate(tree[node].board.clone(), rng),
+        };
+        let root = tree[0].board.clone();
+        let mut current = Some(node);
+        while let Some(node) = current {
+            let node = &mut tree[node];
+            node.visits += 1;
+            if root.same_side(node.mover, winner) {
+                node.wins += 1.;
+            }
+            current = node.parent;
+        }
+    }
+
+    fn select_child(&self, tree: &[Node], node: usize) -> usize {
+        let log_visits = f64::from(tree[node].visits).ln();
+        let uct = |child: usize| {
+            let child = &tree[child];
+            let visits = f64::from(child.visits);
+            child.wins / visits + self.exploration * (log_visits / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+
+    fn simulate(&self, mut board: Board, rng: &mut StdRng) -> prelude::Player {
+        loop {
+            let moves: MoveList = board.moves().collect();
+            let mv = match self.rollout {
+                Rollout::Biased { eval, epsilon } if !rng.random_bool(epsilon) => {
+                    moves.iter().max_by_key(|&&mv| {
+                        let undo = board.apply(&board.turn(mv)).unwrap();
+                        let score = eval(&board);
+                        board.undo(undo);
+                        score
+                    })
+                }
+                _ => moves.choose(rng),
+            };
+            let Some(&mv) = mv else {
+                return board.previous_player();
+            };
+            if mv.is_final() {
+                return board.current_player();
+            }
+            board.apply(&board.turn(mv)).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::prelude::{Construction, Position};
+
+    fn mcts(rollout: Rollout) -> Mcts {
+        Mcts {
+            budget: Budget::Iterations(2000),
+            exploration: std::f64::consts::SQRT_2,
+            rollout,
+            seed: Some(42),
+        }
+    }
+
+    fn winning_board() -> Board {
+        let mut board = Board::new();
+        board.player1_meeple = [Position::new(1, 1), Position::new(4, 4)].into();
+        board.player2_meeple = [Position::new(0, 4), Position::new(4, 0)].into();
+        board.set_construction(Position::new(1, 1), Construction::SecondLevel);
+        board.set_construction(Position::new(2, 2), Construction::ThirdLevel);
+        board
+    }
+
+    #[test]
+    fn takes_immediate_win() {
+        for rollout in [
+            Rollout::Random,
+            Rollout::Biased {
+                eval: crate::player::greedy::elevation,
+                epsilon: 0.2,
+            },
+        ] {
+            let turn = mcts(rollout).play(&winning_board(), None);
+            assert!(matches!(
+                turn,
+                Decision::Play(Turn::FinalMove { start, end })
+                    if start == Position::new(1, 1) && end == Position::new(2, 2)
+            ));
+        }
+    }
+
+    #[test]
+    fn seeded_search_is_reproducible() {
+        let mut board = Board::new();
+        board.player1_meeple = [Position::new(1, 1), Position::new(3, 3)].into();
+        board.player2_meeple = [Position::new(1, 3), Position::new(3, 1)].into();
+        let first = format!("{:?}", mcts(Rollout::Random).play(&board, None));
+        let second = format!("{:?}", mcts(Rollout::Random).play(&board, None));
+        assert_eq!(first, second);
+    }
+}
@@ -1,22 +1,93 @@
+mod alphabeta;
+pub mod analysis;
+pub mod arena;
+pub mod engine;
 mod greedy;
+mod mcts;
 mod random;
+pub mod remote;
+pub mod tournament;
+mod transposition;
 
-use crate::game::{board::Board, turn::Turn};
+use std::time::Duration;
+
+use crate::game::{board::Board, clock::Clock, turn::Turn};
+
+/// What a player does when it is their turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Play(Turn),
+    Resign,
+    /// Both sides agreed to a draw.
+    AgreeDraw,
+}
 
 pub trait Player {
-    fn play(&self, board: &Board) -> Turn;
+    /// `clock` holds the remaining time of every player in timed games.
+    fn play(&self, board: &Board, clock: Option<&Clock>) -> Decision;
+
+    /// Called after every turn of the game, theirs included, with the board
+    /// it was played on.
+    fn turn_played(&self, _board: &Board, _turn: &Turn) {}
+
+    /// Called once with the last board when the game ends, however it ended.
+    fn game_over(&self, _board: &Board) {}
+
+    /// Why the player forfeited, for players which can fail such as engines.
+    fn error(&self) -> Option<String> {
+        None
+    }
 }
 
-pub const PLAYER_TYPE: [&str; 3] = ["Human", "Random", "Greedy hill climber"];
+pub const PLAYER_TYPE: [&str; 5] = [
+    "Human",
+    "Random",
+    "Greedy hill climber",
+    "Alpha-beta search",
+    "Monte Carlo tree search",
+];
+/// Short names used to pick players from the command line.
+pub const PLAYER_ID: [&str; 5] = ["human", "random", "greedy", "alphabeta", "mcts"];
 pub type PlayerOrHuman = Option<Box<dyn Player>>;
 
 pub fn get_player_from_selection(selection: usize) -> PlayerOrHuman {
+    build_player(selection, Duration::from_secs(1))
+}
+
+/// Parses `<id>[:<milliseconds>]`, the optional part being the thinking time
+/// of searching players, or `engine:<command>` to start an external engine.
+pub fn get_player_from_spec(spec: &str) -> Option<PlayerOrHuman> {
+    if let Some(command) = spec.strip_prefix("engine:") {
+        let engine = engine::ExternalEngine::from_command_line(command, Duration::from_secs(1));
+        return Some(Some(Box::new(engine.ok()?)));
+    }
+    let (id, budget) = match spec.split_once(':') {
+        Some((id, millis)) => (id, Duration::from_millis(millis.parse().ok()?)),
+        None => (spec, Duration::from_secs(1)),
+    };
+    let selection = PLAYER_ID.iter().position(|&name| name == id)?;
+    Some(build_player(selection, budget))
+}
+
+fn build_player(selection: usize, budget: Duration) -> PlayerOrHuman {
     match selection {
         0 => None,
         1 => Some(Box::new(random::RandomPlayer)),
         2 => Some(Box::new(greedy::Greedy {
             eval: greedy::elevation,
         })),
+        3 => Some(Box::new(alphabeta::AlphaBeta {
+            eval: alphabeta::elevation_difference,
+            max_depth: 64,
+            time_budget: budget,
+            table: transposition::TranspositionTable::shared(1 << 18),
+        })),
+        4 => Some(Box::new(mcts::Mcts {
+            budget: mcts::Budget::Time(budget),
+            exploration: std::f64::consts::SQRT_2,
+            rollout: mcts::Rollout::Random,
+            seed: None,
+        })),
         _ => panic!("Not possible"),
     }
 }
@@ -1,12 +1,16 @@
-use super::Player;
+use super::{Decision, Player};
 use rand::{prelude::*, rng};
 
 pub struct RandomPlayer;
 
 impl Player for RandomPlayer {
-    fn play(&self, board: &crate::game::board::Board) -> crate::game::turn::Turn {
+    fn play(
+        &self,
+        board: &crate::game::board::Board,
+        _clock: Option<&crate::game::clock::Clock>,
+    ) -> Decision {
         let possible_moves = board.possible_move();
 
-        possible_moves.choose(&mut rng()).unwrap().clone()
+        Decision::Play(possible_moves.choose(&mut rng()).unwrap().clone())
     }
 }
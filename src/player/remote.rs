@@ -0,0 +1,508 @@
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+};
+
+use thiserror::Error;
+
+use super::{Decision, Player};
+use crate::game::{board::Board, clock::Clock, error::GameError, prelude, turn::Turn};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A line of the network protocol.
+///
+/// Both sides start with `POMPEI <version>`, then the host sends
+/// `SETUP <side> <fen>` with the player of the guest (1 or 2) and the starting
+/// board. Afterwards either side may send:
+///
+/// ```text
+/// MOVE <turn>   the turn it just played, in record notation
+/// RESIGN
+/// DRAW          offers a draw, or accepts the standing offer of the other
+///               side; an offer stands until the other side plays a turn
+/// CHAT <text>
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hello(u32),
+    Setup(prelude::Player, Board),
+    Move(Turn),
+    Resign,
+    Draw,
+    Chat(String),
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Hello(version) => write!(f, "POMPEI {version}"),
+            Message::Setup(side, board) => {
+                write!(f, "SETUP {} {}", *side as usize + 1, board.to_fen())
+            }
+            Message::Move(turn) => write!(f, "MOVE {turn}"),
+            Message::Resign => write!(f, "RESIGN"),
+            Message::Draw => write!(f, "DRAW"),
+            Message::Chat(text) => write!(f, "CHAT {text}"),
+        }
+    }
+}
+
+impl FromStr for Message {
+    type Err = RemoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RemoteError::InvalidMessage(s.to_string());
+        let (command, argument) = s.split_once(' ').unwrap_or((s, ""));
+        match command {
+            "POMPEI" => argument.parse().map(Message::Hello).map_err(|_| invalid()),
+            "SETUP" => {
+                let (side, fen) = argument.split_once(' ').ok_or_else(invalid)?;
+                let side = match side {
+                    "1" => prelude::Player::Player1,
+                    "2" => prelude::Player::Player2,
+                    _ => return Err(invalid()),
+                };
+                let board = Board::from_fen(fen).map_err(|_| invalid())?;
+                Ok(Message::Setup(side, board))
+            }
+            "MOVE" => argument.parse().map(Message::Move).map_err(|_| invalid()),
+            "RESIGN" if argument.is_empty() => Ok(Message::Resign),
+            "DRAW" if argument.is_empty() => Ok(Message::Draw),
+            "CHAT" => Ok(Message::Chat(argument.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RemoteError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("the connection was closed")]
+    Closed,
+    #[error("invalid message {0:?}")]
+    InvalidMessage(String),
+    #[error("unexpected message {0:?}")]
+    Unexpected(String),
+    #[error("unsupported protocol version {0}")]
+    Version(u32),
+    #[error("the other side played {0}: {1}")]
+    IllegalTurn(Turn, GameError),
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, RemoteError> {
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        connection.send(&Message::Hello(PROTOCOL_VERSION))?;
+        match connection.receive()? {
+            Message::Hello(PROTOCOL_VERSION) => Ok(connection),
+            Message::Hello(version) => Err(RemoteError::Version(version)),
+            message => Err(RemoteError::Unexpected(message.to_string())),
+        }
+    }
+
+    fn send(&mut self, message: &Message) -> Result<(), RemoteError> {
+        writeln!(self.writer, "{message}")?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Message, RemoteError> {
+        read_message(&mut self.reader)
+    }
+
+    // Keeps the writing half, messages being read on another thread so that
+    // the game can go on while waiting for them.
+    fn split(self) -> (TcpStream, Receiver<Result<Message, RemoteError>>) {
+        let Connection { mut reader, writer } = self;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let message = read_message(&mut reader);
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        (writer, receiver)
+    }
+}
+
+fn read_message(reader: &mut BufReader<TcpStream>) -> Result<Message, RemoteError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(RemoteError::Closed);
+    }
+    line.trim_end().parse()
+}
+
+/// The player on the other end of a TCP connection.
+///
+/// Turns played locally are sent from `turn_played`. `play` waits for the
+/// remote turn, and `poll` checks for it without waiting, so that the local
+/// side can chat, offer a draw or resign meanwhile. Any failure, such as an
+/// illegal turn or a closed connection, counts as a resignation.
+pub struct RemotePlayer {
+    state: Mutex<State>,
+}
+
+struct State {
+    // The player on the other side.
+    side: prelude::Player,
+    writer: TcpStream,
+    incoming: Receiver<Result<Message, RemoteError>>,
+    // Last board known to both sides.
+    board: Board,
+    offered_draw: bool,
+    draw_offered: bool,
+    // Whether the game ended for the other side, which then expects no message.
+    ended: bool,
+    agreed_draw: bool,
+    chat: Vec<String>,
+    error: Option<RemoteError>,
+}
+
+impl RemotePlayer {
+    /// Waits for a guest on `listener` to start a game from `board`, the guest
+    /// playing `side`.
+    pub fn host(
+        listener: &TcpListener,
+        board: &Board,
+        side: prelude::Player,
+    ) -> Result<Self, RemoteError> {
+        let (stream, _) = listener.accept()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Setup(side, board.clone()))?;
+        Ok(Self::new(connection, side, board.clone()))
+    }
+
+    /// Joins the game hosted at `address`, returning the side played locally
+    /// and the starting board.
+    pub fn join(
+        address: impl ToSocketAddrs,
+    ) -> Result<(Self, prelude::Player, Board), RemoteError> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        match connection.receive()? {
+            Message::Setup(side, board) => {
+                // Network games have two players, the host playing the other side.
+                let host = prelude::Player::ALL[1 - side as usize];
+                Ok((Self::new(connection, host, board.clone()), side, board))
+            }
+            message => Err(RemoteError::Unexpected(message.to_string())),
+        }
+    }
+
+    fn new(connection: Connection, side: prelude::Player, board: Board) -> Self {
+        let (writer, incoming) = connection.split();
+        Self {
+            state: Mutex::new(State {
+                side,
+                writer,
+                incoming,
+                board,
+                offered_draw: false,
+                draw_offered: false,
+                ended: false,
+                agreed_draw: false,
+                chat: vec![],
+                error: None,
+            }),
+        }
+    }
+
+    pub fn say(&self, text: &str) -> Result<(), RemoteError> {
+        let text = text.replace(['\r', '\n'], " ");
+        self.state.lock().unwrap().send(&Message::Chat(text))
+    }
+
+    /// Offers a draw, or accepts the one offered by the other side, after which
+    /// `play` returns `Decision::AgreeDraw`.
+    pub fn offer_draw(&self) -> Result<(), RemoteError> {
+        let mut state = self.state.lock().unwrap();
+        if state.draw_offered {
+            state.ended = true;
+            state.agreed_draw = true;
+        } else {
+            state.offered_draw = true;
+        }
+        state.send(&Message::Draw)
+    }
+
+    /// Whether the other side offered a draw since its last turn.
+    pub fn draw_offered(&self) -> bool {
+        self.state.lock().unwrap().draw_offered
+    }
+
+    /// Chat messages received so far.
+    pub fn chat(&self) -> Vec<String> {
+        self.state.lock().unwrap().chat.clone()
+    }
+
+    /// Like `play`, without waiting for the other side: returns its decision
+    /// if it made one, after handling the chat and draw offers received.
+    pub fn poll(&self) -> Option<Decision> {
+        self.decide(false)
+    }
+
+    fn decide(&self, wait: bool) -> Option<Decision> {
+        let mut state = self.state.lock().unwrap();
+        state.play(wait).unwrap_or_else(|error| {
+            state.ended = true;
+            state.error = Some(error);
+            Some(Decision::Resign)
+        })
+    }
+}
+
+impl State {
+    fn send(&mut self, message: &Message) -> Result<(), RemoteError> {
+        writeln!(self.writer, "{message}")?;
+        Ok(())
+    }
+
+    // The decision of the other side, `None` if it made none yet and `wait`
+    // is false.
+    fn play(&mut self, wait: bool) -> Result<Option<Decision>, RemoteError> {
+        if self.agreed_draw {
+            return Ok(Some(Decision::AgreeDraw));
+        }
+        if self.ended {
+            return Ok(Some(Decision::Resign));
+        }
+        loop {
+            let message = if wait {
+                self.incoming.recv().map_err(|_| RemoteError::Closed)?
+            } else {
+                match self.incoming.try_recv() {
+                    Ok(message) => message,
+                    Err(TryRecvError::Empty) => return Ok(None),
+                    Err(TryRecvError::Disconnected) => Err(RemoteError::Closed),
+                }
+            };
+            match message? {
+                Message::Move(turn) => {
+                    self.board = self
+                        .board
+                        .action(&turn)
+                        .map_err(|error| RemoteError::IllegalTurn(turn.clone(), error))?;
+                    self.offered_draw = false;
+                    return Ok(Some(Decision::Play(turn)));
+                }
+                Message::Resign => {
+                    self.ended = true;
+                    return Ok(Some(Decision::Resign));
+                }
+                Message::Draw if self.offered_draw => {
+                    self.ended = true;
+                    self.agreed_draw = true;
+                    return Ok(Some(Decision::AgreeDraw));
+                }
+                Message::Draw => self.draw_offered = true,
+                Message::Chat(text) => self.chat.push(text),
+                message => return Err(RemoteError::Unexpected(message.to_string())),
+            }
+        }
+    }
+
+    // Sends the turn played locally on `board`.
+    fn turn_played(&mut self, board: &Board, turn: &Turn) -> Result<(), RemoteError> {
+        // The turns of the other side came from it.
+        if self.ended || board.current_player() == self.side {
+            return Ok(());
+        }
+        self.board = board.action(turn).unwrap();
+        self.draw_offered = false;
+        self.send(&Message::Move(turn.clone()))
+    }
+
+    fn game_over(&mut self, board: &Board) -> Result<(), RemoteError> {
+        if self.ended {
+            return Ok(());
+        }
+        self.ended = true;
+        // Games ending on a turn ended for the other side too.
+        if *board == self.board && board.outcome().is_some() {
+            return Ok(());
+        }
+        // The local side resigned or left.
+        self.send(&Message::Resign)
+    }
+}
+
+impl Player for RemotePlayer {
+    fn play(&self, _board: &Board, _clock: Option<&Clock>) -> Decision {
+        // Waiting, the other side always decides in the end.
+        self.decide(true).unwrap_or(Decision::Resign)
+    }
+
+    fn turn_played(&self, board: &Board, turn: &Turn) {
+        let mut state = self.state.lock().unwrap();
+        if let Err(error) = state.turn_played(board, turn) {
+            state.error = Some(error);
+        }
+    }
+
+    fn game_over(&self, board: &Board) {
+        let mut state = self.state.lock().unwrap();
+        if let Err(error) = state.game_over(board) {
+            state.error = Some(error);
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.error.as_ref().map(ToString::to_string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::*;
+    use crate::player::{arena, greedy, random::RandomPlayer};
+
+    fn listen() -> (TcpListener, std::net::SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        (listener, address)
+    }
+
+    #[test]
+    fn messages() {
+        for line in [
+            "POMPEI 1",
+            "SETUP 2 00000/00000/00000/00000/00000 a",
+            "MOVE S c3 d3",
+            "MOVE c3-d4^e5",
+            "RESIGN",
+            "DRAW",
+            "CHAT good game",
+        ] {
+            assert_eq!(line.parse::<Message>().unwrap().to_string(), line);
+        }
+        for line in [
+            "",
+            "MOVE",
+            "SETUP 3 00000/00000/00000/00000/00000 a",
+            "DRAW now",
+        ] {
+            assert!(matches!(
+                line.parse::<Message>(),
+                Err(RemoteError::InvalidMessage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn game_over_localhost() {
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let remote =
+                RemotePlayer::host(&listener, &Board::new(), prelude::Player::Player2).unwrap();
+            let greedy = greedy::Greedy {
+                eval: greedy::elevation,
+            };
+            arena::play_game(&greedy, &remote, 200)
+        });
+        let (remote, side, board) = RemotePlayer::join(address).unwrap();
+        assert_eq!(side, prelude::Player::Player2);
+        assert_eq!(board, Board::new());
+        let guest = arena::play_game(&remote, &RandomPlayer, 200);
+        assert_eq!(remote.error(), None);
+        assert_eq!(host.join().unwrap(), guest);
+    }
+
+    #[test]
+    fn chat_and_draw() {
+        let first = Board::new().possible_move().swap_remove(0);
+        let after_first = Board::new().action(&first).unwrap();
+        let second = after_first.possible_move().swap_remove(0);
+        let (host_first, guest_board, guest_second) =
+            (first.clone(), after_first.clone(), second.clone());
+
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let remote =
+                RemotePlayer::host(&listener, &Board::new(), prelude::Player::Player2).unwrap();
+            remote.turn_played(&Board::new(), &host_first);
+            assert_eq!(remote.play(&after_first, None), Decision::Play(second));
+            assert_eq!(remote.chat(), ["good luck"]);
+            assert!(remote.draw_offered());
+            remote.offer_draw().unwrap();
+            assert_eq!(remote.poll(), Some(Decision::AgreeDraw));
+        });
+        let (remote, _, board) = RemotePlayer::join(address).unwrap();
+        assert_eq!(remote.play(&board, None), Decision::Play(first.clone()));
+        // The turn of the other side is not sent back.
+        remote.turn_played(&board, &first);
+        remote.say("good luck").unwrap();
+        remote.offer_draw().unwrap();
+        remote.turn_played(&guest_board, &guest_second);
+        assert_eq!(remote.play(&guest_board, None), Decision::AgreeDraw);
+        host.join().unwrap();
+        assert_eq!(remote.error(), None);
+    }
+
+    #[test]
+    fn sends_turn_played() {
+        // Moving twice to a neighbor of the start reaches the same board as
+        // moving there once.
+        let board = Board::from_fen("0a0000/00000/000a00/00000/0b0b000 a Artemis/-").unwrap();
+        let double_move = board
+            .possible_move()
+            .into_iter()
+            .find(|turn| {
+                matches!(turn, Turn::DoubleMove { start, end, build: Some(_), .. }
+                    if prelude::Position::are_neighbors(*start, *end))
+            })
+            .unwrap();
+        let played = double_move.clone();
+
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let remote = RemotePlayer::host(&listener, &board, prelude::Player::Player2).unwrap();
+            remote.turn_played(&board, &played);
+        });
+        let (remote, _, board) = RemotePlayer::join(address).unwrap();
+        assert_eq!(remote.play(&board, None), Decision::Play(double_move));
+        host.join().unwrap();
+    }
+
+    #[test]
+    fn poll_during_local_turn() {
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let remote =
+                RemotePlayer::host(&listener, &Board::new(), prelude::Player::Player2).unwrap();
+            // Nothing was received yet.
+            assert_eq!(remote.poll(), None);
+            remote.say("hurry up").unwrap();
+            remote.play(&Board::new(), None)
+        });
+        let (remote, _, board) = RemotePlayer::join(address).unwrap();
+        while remote.chat().is_empty() {
+            assert_eq!(remote.poll(), None);
+        }
+        assert_eq!(remote.chat(), ["hurry up"]);
+        remote.game_over(&board.resign());
+        assert_eq!(host.join().unwrap(), Decision::Resign);
+        assert_eq!(remote.error(), None);
+    }
+}
@@ -0,0 +1,318 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use super::{Player, arena};
+use crate::game::prelude;
+
+/// A player taking part in a tournament, named by its command line spec.
+pub struct Entrant {
+    pub name: String,
+    pub player: Box<dyn Player>,
+}
+
+/// A finished tournament game, between `first` (placing their workers first)
+/// and `second`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Played {
+    pub first: String,
+    pub second: String,
+    /// `None` for a game stopped at the turn limit.
+    pub winner: Option<prelude::Player>,
+}
+
+impl Played {
+    fn involves(&self, name: &str, opponent: &str) -> bool {
+        (self.first == name && self.second == opponent)
+            || (self.first == opponent && self.second == name)
+    }
+
+    // Points scored by `name` in this game.
+    fn points(&self, name: &str) -> f64 {
+        let player = if self.first == name {
+            prelude::Player::Player1
+        } else {
+            prelude::Player::Player2
+        };
+        match self.winner {
+            Some(winner) if winner == player => 1.,
+            Some(_) => 0.,
+            None => 0.5,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ResultsError {
+    #[error("line {0}: expected `<first> <second> <1-0|0-1|1/2-1/2>`")]
+    InvalidLine(usize),
+}
+
+/// All the games played in past tournaments, stored one per line as
+/// `<first> <second> <result>`, so that new entrants can be rated against the
+/// existing pool.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Results {
+    pub games: Vec<Played>,
+}
+
+impl Results {
+    /// Names of all the players with at least one game, in order of appearance.
+    pub fn players(&self) -> Vec<&str> {
+        let mut players: Vec<&str> = vec![];
+        for game in &self.games {
+            for name in [&game.first, &game.second] {
+                if !players.contains(&name.as_str()) {
+                    players.push(name);
+                }
+            }
+        }
+        players
+    }
+
+    pub fn games_between(&self, name: &str, opponent: &str) -> usize {
+        self.games
+            .iter()
+            .filter(|game| game.involves(name, opponent))
+            .count()
+    }
+
+    /// Points scored by `name` against `opponent`.
+    pub fn points(&self, name: &str, opponent: &str) -> f64 {
+        self.games
+            .iter()
+            .filter(|game| game.involves(name, opponent))
+            .map(|game| game.points(name))
+            .sum()
+    }
+
+    /// Bradley-Terry ratings on the Elo scale, averaging 1500, best first.
+    ///
+    /// Every pairing counts an extra virtual draw, so that players who won or
+    /// lost all their games still get a finite rating.
+    pub fn ratings(&self) -> Vec<(String, f64)> {
+        let players = self.players();
+        let pairings: Vec<Vec<f64>> = players
+            .iter()
+            .map(|name| {
+                players
+                    .iter()
+                    .map(|opponent| match self.games_between(name, opponent) {
+                        0 => 0.,
+                        games => games as f64 + 1.,
+                    })
+                    .collect()
+            })
+            .collect();
+        let scores: Vec<f64> = players
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                players
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| i != j && pairings[i][j] > 0.)
+                    .map(|(_, opponent)| self.points(name, opponent) + 0.5)
+                    .sum()
+            })
+            .collect();
+
+        // Minorization-maximization updates, see Hunter, "MM algorithms for
+        // generalized Bradley-Terry models" (2004).
+        let mut strengths = vec![1.; players.len()];
+        for _ in 0..1000 {
+            let mut updated: Vec<f64> = (0..players.len())
+                .map(|i| {
+                    let denominator: f64 = (0..players.len())
+                        .filter(|&j| j != i)
+                        .map(|j| pairings[i][j] / (strengths[i] + strengths[j]))
+                        .sum();
+                    if denominator == 0. {
+                        strengths[i]
+                    } else {
+                        scores[i] / denominator
+                    }
+                })
+                .collect();
+            let mean_log =
+                updated.iter().map(|strength| strength.ln()).sum::<f64>() / updated.len() as f64;
+            updated
+                .iter_mut()
+                .for_each(|strength| *strength /= mean_log.exp());
+            let converged = strengths
+                .iter()
+                .zip(&updated)
+                .all(|(old, new)| (old - new).abs() < 1e-9);
+            strengths = updated;
+            if converged {
+                break;
+            }
+        }
+
+        let mut ratings: Vec<(String, f64)> = players
+            .iter()
+            .zip(strengths)
+            .map(|(name, strength)| (name.to_string(), 1500. + 400. * strength.log10()))
+            .collect();
+        ratings.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ratings
+    }
+
+    /// Ratings followed by the points each player scored against every other.
+    pub fn crosstable(&self) -> String {
+        let ratings = self.ratings();
+        let width = ratings
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max(6);
+        let mut table = format!("{:>3} {:width$} {:>6}", "", "player", "elo");
+        for rank in 1..=ratings.len() {
+            table += &format!(" {rank:>9}");
+        }
+        for (rank, (name, rating)) in ratings.iter().enumerate() {
+            table += &format!("\n{:>3} {name:width$} {rating:>6.0}", rank + 1);
+            for (opponent, _) in &ratings {
+                let cell = match self.games_between(name, opponent) {
+                    _ if name == opponent => "-".to_string(),
+                    0 => String::new(),
+                    games => format!("{}/{games}", self.points(name, opponent)),
+                };
+                table += &format!(" {cell:>9}");
+            }
+        }
+        table
+    }
+}
+
+impl Display for Results {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for game in &self.games {
+            let result = match game.winner {
+                Some(prelude::Player::Player1) => "1-0",
+                Some(_) => "0-1",
+                None => "1/2-1/2",
+            };
+            writeln!(f, "{} {} {result}", game.first, game.second)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Results {
+    type Err = ResultsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let games = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let invalid = ResultsError::InvalidLine(index + 1);
+                let [first, second, result] = line.split_whitespace().collect::<Vec<_>>()[..]
+                else {
+                    return Err(invalid);
+                };
+                let winner = match result {
+                    "1-0" => Some(prelude::Player::Player1),
+                    "0-1" => Some(prelude::Player::Player2),
+                    "1/2-1/2" => None,
+                    _ => return Err(invalid),
+                };
+                Ok(Played {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    winner,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { games })
+    }
+}
+
+/// Plays every pairing of `entrants` until it has `games` games in `results`,
+/// alternating who places their workers first. `played` is called after each
+/// new game, e.g. to save the results as they come.
+pub fn round_robin(
+    entrants: &[Entrant],
+    games: usize,
+    max_turns: usize,
+    results: &mut Results,
+    mut played: impl FnMut(&Results),
+) {
+    for (index, entrant) in entrants.iter().enumerate() {
+        for opponent in &entrants[index + 1..] {
+            for game in results.games_between(&entrant.name, &opponent.name)..games {
+                let (first, second) = if game % 2 == 0 {
+                    (entrant, opponent)
+                } else {
+                    (opponent, entrant)
+                };
+                let result =
+                    arena::play_game(first.player.as_ref(), second.player.as_ref(), max_turns);
+                results.games.push(Played {
+                    first: first.name.clone(),
+                    second: second.name.clone(),
+                    winner: result.winner,
+                });
+                played(results);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::player::{greedy, random::RandomPlayer};
+
+    #[test]
+    fn results_file() {
+        let text = "greedy random 1-0\nrandom greedy 0-1\nmcts:100 greedy 1/2-1/2\n";
+        let results: Results = text.parse().unwrap();
+        assert_eq!(results.to_string(), text);
+        assert_eq!(results.players(), ["greedy", "random", "mcts:100"]);
+        assert_eq!(results.games_between("random", "greedy"), 2);
+        assert_eq!(results.points("greedy", "mcts:100"), 0.5);
+        assert_eq!(
+            "greedy random\n".parse::<Results>(),
+            Err(ResultsError::InvalidLine(1))
+        );
+    }
+
+    #[test]
+    fn ratings() {
+        let results: Results = "a b 1-0\nb a 0-1\nb c 1-0\nc b 1/2-1/2\na c 1/2-1/2\n"
+            .parse()
+            .unwrap();
+        let ratings = results.ratings();
+        let names: Vec<&str> = ratings.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        let mean = ratings.iter().map(|(_, rating)| rating).sum::<f64>() / 3.;
+        assert!((mean - 1500.).abs() < 1e-6);
+        assert!(results.crosstable().lines().nth(1).unwrap().contains("2/2"));
+    }
+
+    #[test]
+    fn only_plays_missing_games() {
+        let entrants = [
+            Entrant {
+                name: "greedy".to_string(),
+                player: Box::new(greedy::Greedy {
+                    eval: greedy::elevation,
+                }),
+            },
+            Entrant {
+                name: "random".to_string(),
+                player: Box::new(RandomPlayer),
+            },
+        ];
+        let mut results: Results = "random greedy 1-0\n".parse().unwrap();
+        let mut saved = 0;
+        round_robin(&entrants, 3, 200, &mut results, |_| saved += 1);
+        assert_eq!(saved, 2);
+        assert_eq!(results.games_between("greedy", "random"), 3);
+        assert_eq!(results.games[1].first, "random");
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+
+use crate::game::turn::Turn;
+
+/// How the stored score relates to the exact value of the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, the value is at least the score.
+    Lower,
+    /// The search failed low, the value is at most the score.
+    Upper,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u32,
+    pub score: i64,
+    pub bound: Bound,
+    pub best_move: Option<Turn>,
+    generation: u8,
+}
+
+/// Fixed-size table of search results, indexed by `Board::hash`.
+///
+/// An entry is replaced by a search of the same position, a deeper search, or
+/// any search once it is left over from a previous `new_search`.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    generation: u8,
+}
+
+pub type SharedTable = Arc<Mutex<TranspositionTable>>;
+
+impl TranspositionTable {
+    /// Creates a table holding `size` entries, rounded up to a power of two.
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: vec![None; size.next_power_of_two()],
+            generation: 0,
+        }
+    }
+
+    pub fn shared(size: usize) -> SharedTable {
+        Arc::new(Mutex::new(Self::new(size)))
+    }
+
+    fn index(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.entries[self.index(key)]
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+
+    pub fn store(
+        &mut self,
+        key: u64,
+        depth: u32,
+        score: i64,
+        bound: Bound,
+        best_move: Option<Turn>,
+    ) {
+        let generation = self.generation;
+        let index = self.index(key);
+        let slot = &mut self.entries[index];
+        let replace = match slot {
+            None => true,
+            Some(entry) => {
+                entry.key == key || entry.generation != generation || depth >= entry.depth
+            }
+        };
+        if replace {
+            *slot = Some(Entry {
+                key,
+                depth,
+                score,
+                bound,
+                best_move,
+                generation,
+            });
+        }
+    }
+
+    /// Marks existing entries as stale, so that they are replaced first.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replacement() {
+        let mut table = TranspositionTable::new(3);
+        assert!(table.probe(1).is_none());
+        table.store(1, 4, 10, Bound::Exact, None);
+        assert_eq!(table.probe(1).unwrap().score, 10);
+        assert!(table.probe(5).is_none());
+
+        // Same slot, shallower search of another position.
+        table.store(5, 2, 20, Bound::Lower, None);
+        assert_eq!(table.probe(1).unwrap().depth, 4);
+        table.store(5, 4, 20, Bound::Lower, None);
+        assert!(table.probe(1).is_none());
+        assert_eq!(table.probe(5).unwrap().bound, Bound::Lower);
+
+        table.store(5, 1, 30, Bound::Upper, None);
+        assert_eq!(table.probe(5).unwrap().score, 30);
+
+        table.new_search();
+        table.store(1, 0, 40, Bound::Exact, None);
+        assert_eq!(table.probe(1).unwrap().score, 40);
+
+        table.clear();
+        assert!(table.probe(1).is_none());
+    }
+}
@@ -1,46 +1,134 @@
+use std::rc::Rc;
+
 use ratatui::{
-    layout::{Constraint, Layout, Margin},
+    layout::{Constraint, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, Widget},
 };
 
 use crate::game::{
     Game,
-    prelude::{Construction, Player, Position, Tile},
+    prelude::{Construction, Player, Position, Size, Tile},
+    turn::Turn,
 };
 
-pub struct BoardWidget<'a>(pub &'a Game, pub Position);
+/// Color of `player`'s workers, also used for their name in the menu.
+pub fn player_color(player: Player) -> Color {
+    match player {
+        Player::Player1 => Color::Blue,
+        Player::Player2 => Color::Yellow,
+        Player::Player3 => Color::Magenta,
+        Player::Player4 => Color::Cyan,
+    }
+}
+
+// Areas of the tiles of a board of `size` drawn in `area`, row by row.
+fn tile_grid(area: Rect, size: Size) -> Vec<Rc<[Rect]>> {
+    // TODO make tile squarer
+    let board_length = std::cmp::min(area.width, area.height);
+    let board_area = area.inner(Margin {
+        horizontal: (area.width - board_length) / 2,
+        vertical: (area.height - board_length) / 2,
+    });
+
+    Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, size.rows as u32); size.rows])
+        .split(board_area)
+        .iter()
+        .map(|&column| {
+            Layout::default()
+                .direction(ratatui::layout::Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, size.cols as u32); size.cols])
+                .split(column)
+        })
+        .collect()
+}
+
+/// The tile under the terminal cell at `column` and `row`, for a board of
+/// `size` rendered in `area`.
+pub fn position_at(area: Rect, size: Size, column: u16, row: u16) -> Option<Position> {
+    let cell = ratatui::layout::Position::new(column, row);
+    tile_grid(area, size)
+        .iter()
+        .enumerate()
+        .find_map(|(r, tiles)| {
+            let c = tiles.iter().position(|tile| tile.contains(cell))?;
+            Some(Position::new(r, c))
+        })
+}
+
+/// The game, the cursor and the turn to highlight as a hint.
+pub struct BoardWidget<'a>(pub &'a Game, pub Option<Position>, pub Option<&'a Turn>);
+
+// The squares a turn uses, with what happens on them.
+fn hint_squares(turn: &Turn) -> Vec<(Position, &'static str)> {
+    match *turn {
+        Turn::Setup(first, second) => vec![(first, "place"), (second, "place")],
+        Turn::MoveBuild { start, end, build } => {
+            vec![(start, "from"), (end, "to"), (build, "build")]
+        }
+        Turn::FinalMove { start, end } => vec![(start, "from"), (end, "to")],
+        Turn::DoubleMove {
+            start,
+            middle,
+            end,
+            build,
+        } => {
+            let mut squares = vec![(start, "from"), (middle, "via"), (end, "to")];
+            squares.extend(build.map(|build| (build, "build")));
+            squares
+        }
+        Turn::DoubleBuild {
+            start,
+            end,
+            build,
+            second_build,
+        } => vec![
+            (start, "from"),
+            (end, "to"),
+            (build, "build"),
+            (second_build, "build"),
+        ],
+        Turn::DomeBuild { start, end, build } => {
+            vec![(start, "from"), (end, "to"), (build, "dome")]
+        }
+        Turn::BuildMoveBuild {
+            first_build,
+            start,
+            end,
+            build,
+        } => vec![
+            (first_build, "build"),
+            (start, "from"),
+            (end, "to"),
+            (build, "build"),
+        ],
+    }
+}
 
 impl<'a> Widget for BoardWidget<'a> {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
-        // TODO make tile squarer
-        let board_length = std::cmp::min(area.width, area.height);
-        let board_area = area.inner(Margin {
-            horizontal: (area.width - board_length) / 2,
-            vertical: (area.height - board_length) / 2,
-        });
-
-        let grid = Layout::default()
-            .direction(ratatui::layout::Direction::Vertical)
-            .constraints(vec![Constraint::Ratio(1, 5); 5])
-            .split(board_area)
-            .iter()
-            .map(|&column| {
-                Layout::default()
-                    .direction(ratatui::layout::Direction::Horizontal)
-                    .constraints(vec![Constraint::Ratio(1, 5); 5])
-                    .split(column)
-            })
-            .collect::<Vec<_>>();
+        let grid = tile_grid(area, self.0.board().size);
+        let hint = self.2.map(hint_squares).unwrap_or_default();
         for (position, tile) in self.0.board().get_tiles() {
+            let mut roles: Vec<&str> = hint
+                .iter()
+                .filter(|(square, _)| *square == position)
+                .map(|(_, role)| *role)
+                .collect();
+            // Hephaestus builds twice on the same square.
+            roles.dedup();
             TileWidget {
                 tile,
-                cursor: position == self.1,
+                cursor: Some(position) == self.1,
                 selected: self.0.selected().contains(position),
                 selectable: self.0.selectable().contains(position),
+                hint: (!roles.is_empty()).then(|| roles.join("/")),
             }
             .render(grid[position.row()][position.col()], buf);
         }
@@ -52,17 +140,21 @@ struct TileWidget {
     cursor: bool,
     selected: bool,
     selectable: bool,
+    // What the hinted turn does on the tile.
+    hint: Option<String>,
 }
 
 impl Widget for TileWidget {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
         let player = match self.tile.player {
-            Some(Player::Player1) => "P1",
-            Some(Player::Player2) => "P2",
-            None => "  ",
+            Some(player) => Span::styled(
+                format!("P{}", player as usize + 1),
+                Style::default().fg(player_color(player)),
+            ),
+            None => Span::raw("  "),
         };
 
         let construction = match self.tile.construction {
@@ -73,7 +165,9 @@ impl Widget for TileWidget {
             Construction::Dome => "DD",
         };
 
-        let color = if self.selectable {
+        let color = if self.hint.is_some() {
+            Color::White
+        } else if self.selectable {
             Color::Green
         } else {
             Color::Red
@@ -85,18 +179,43 @@ impl Widget for TileWidget {
         };
         let border_type = if self.selected {
             BorderType::Double
+        } else if self.hint.is_some() {
+            BorderType::Thick
         } else {
             BorderType::Plain
         };
 
-        let block = Block::new()
+        let mut block = Block::new()
             .borders(Borders::ALL)
             .border_type(border_type)
             .border_style(Style::default().fg(color));
+        if let Some(hint) = self.hint {
+            block = block.title(hint);
+        }
+
+        Paragraph::new(Line::from(vec![
+            player,
+            Span::raw(format!(" {construction}")),
+        ]))
+        .style(style)
+        .block(block)
+        .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        Paragraph::new(format!("{player} {construction}"))
-            .style(style)
-            .block(block)
-            .render(area, buf);
+    #[test]
+    fn clicked_tile() {
+        // A 5x5 board in a 20x10 area is 10 cells wide, centered horizontally.
+        let area = Rect::new(0, 0, 20, 10);
+        let size = Size { rows: 5, cols: 5 };
+        assert_eq!(position_at(area, size, 5, 0), Some(Position::new(0, 0)));
+        assert_eq!(position_at(area, size, 14, 9), Some(Position::new(4, 4)));
+        assert_eq!(position_at(area, size, 8, 5), Some(Position::new(2, 1)));
+        assert_eq!(position_at(area, size, 4, 5), None);
+        assert_eq!(position_at(area, size, 15, 5), None);
     }
 }
@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use ratatui::{
     DefaultTerminal,
     layout::{Constraint, Layout},
@@ -8,106 +10,126 @@ use ratatui::{
 
 use crate::{
     control::{Message, handle_event},
-    player::{PLAYER_TYPE, PlayerOrHuman, get_player_from_selection},
+    game::prelude::Player,
+    ui::board_widget::player_color,
 };
 
-pub fn player_selection_menu(terminal: &mut DefaultTerminal) -> (PlayerOrHuman, PlayerOrHuman) {
-    let mut list_state = ListState::default().with_selected(Some(0));
-    let player1_selection = loop {
-        terminal
-            .draw(|frame| {
-                let area = frame.area();
-                frame.render_stateful_widget(
-                    crate::ui::menu_widget::MenuWidget { player1: None },
-                    area,
-                    &mut list_state,
-                );
-            })
-            .expect("failed to draw frame");
-        let Some(message) = handle_event() else {
-            continue;
-        };
+/// Returns the indices in `choices` picked for each of the `players` players.
+pub fn player_selection_menu(
+    terminal: &mut DefaultTerminal,
+    choices: &[&str],
+    players: usize,
+) -> Vec<usize> {
+    let mut selected = vec![];
+    while selected.len() < players {
+        let mut list_state = ListState::default().with_selected(Some(0));
+        let selection = loop {
+            terminal
+                .draw(|frame| {
+                    let area = frame.area();
+                    frame.render_stateful_widget(
+                        MenuWidget {
+                            choices,
+                            players,
+                            selected: &selected,
+                        },
+                        area,
+                        &mut list_state,
+                    );
+                })
+                .expect("failed to draw frame");
+            let Some(message) = handle_event() else {
+                continue;
+            };
 
-        match message {
-            Message::Up => {
-                list_state.select_previous();
-            }
-            Message::Down => {
-                list_state.select_next();
+            match message {
+                Message::Up => {
+                    list_state.select_previous();
+                }
+                Message::Down => {
+                    list_state.select_next();
+                }
+                Message::Select => {
+                    break list_state.selected().unwrap();
+                }
+                Message::Click { column, row } => {
+                    let area = terminal.get_frame().area();
+                    if let Some(choice) = MenuWidget::choice_at(
+                        area,
+                        choices.len(),
+                        players,
+                        selected.len(),
+                        list_state.offset(),
+                        column,
+                        row,
+                    ) {
+                        break choice;
+                    }
+                }
+                _ => {}
             }
-            Message::Select => {
-                break list_state.selected().unwrap();
-            }
-            _ => {}
-        }
-    };
-
-    let mut list_state = ListState::default().with_selected(Some(0));
-    loop {
-        terminal
-            .draw(|frame| {
-                let area = frame.area();
-                frame.render_stateful_widget(
-                    MenuWidget {
-                        player1: Some(player1_selection),
-                    },
-                    area,
-                    &mut list_state,
-                );
-            })
-            .expect("failed to draw frame");
-        let Some(message) = handle_event() else {
-            continue;
         };
-
-        match message {
-            Message::Up => {
-                list_state.select_previous();
-            }
-            Message::Down => {
-                list_state.select_next();
-            }
-            Message::Select => {
-                return (
-                    get_player_from_selection(player1_selection),
-                    get_player_from_selection(list_state.selected().unwrap()),
-                );
-            }
-            _ => {}
-        }
+        selected.push(selection);
     }
+    selected
 }
 
-pub struct MenuWidget {
-    pub(crate) player1: Option<usize>,
+pub struct MenuWidget<'a> {
+    pub(crate) choices: &'a [&'a str],
+    pub(crate) players: usize,
+    /// Choices of the players before the one currently picking.
+    pub(crate) selected: &'a [usize],
 }
 
-impl StatefulWidget for MenuWidget {
-    type State = ListState;
-
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let layout = Layout::default()
+impl MenuWidget<'_> {
+    // One column per player.
+    fn columns(area: Rect, players: usize) -> Rc<[Rect]> {
+        Layout::default()
             .direction(ratatui::layout::Direction::Horizontal)
-            .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(area);
-        let player1_list = List::new(PLAYER_TYPE)
-            .block(Block::bordered().title("Player 1"))
-            .highlight_style(Style::new().reversed())
-            .highlight_symbol(">>")
-            .repeat_highlight_symbol(true);
-        let player2_list = player1_list
-            .clone()
-            .block(Block::bordered().title("Player 2"));
+            .constraints(vec![Constraint::Ratio(1, players as u32); players])
+            .split(area)
+    }
 
-        if let Some(player1_selection) = self.player1 {
-            let mut player1_selection = ListState::default().with_selected(Some(player1_selection));
-            StatefulWidget::render(player1_list, layout[0], buf, &mut player1_selection);
+    /// The choice clicked at `column` and `row` in the list of `player`,
+    /// scrolled down by `offset`, if any.
+    fn choice_at(
+        area: Rect,
+        choices: usize,
+        players: usize,
+        player: usize,
+        offset: usize,
+        column: u16,
+        row: u16,
+    ) -> Option<usize> {
+        let list = Block::bordered().inner(Self::columns(area, players)[player]);
+        if !list.contains(ratatui::layout::Position::new(column, row)) {
+            return None;
+        }
+        Some(offset + (row - list.y) as usize).filter(|&choice| choice < choices)
+    }
+}
 
-            StatefulWidget::render(player2_list, layout[1], buf, state);
-        } else {
-            StatefulWidget::render(player1_list, layout[0], buf, state);
+impl StatefulWidget for MenuWidget<'_> {
+    type State = ListState;
 
-            Widget::render(player2_list, layout[1], buf);
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let layout = Self::columns(area, self.players);
+        for (index, player) in Player::ALL[..self.players].iter().enumerate() {
+            let list = List::new(self.choices.iter().copied())
+                .block(Block::bordered().title(player.to_string().fg(player_color(*player))))
+                .highlight_style(Style::new().reversed())
+                .highlight_symbol(">>")
+                .repeat_highlight_symbol(true);
+            match self.selected.get(index) {
+                Some(&selection) => {
+                    let mut selection = ListState::default().with_selected(Some(selection));
+                    StatefulWidget::render(list, layout[index], buf, &mut selection);
+                }
+                None if index == self.selected.len() => {
+                    StatefulWidget::render(list, layout[index], buf, state);
+                }
+                None => Widget::render(list, layout[index], buf),
+            }
         }
     }
 }
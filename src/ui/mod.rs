@@ -1,17 +1,186 @@
 mod board_widget;
 pub mod menu_widget;
 
-use board_widget::BoardWidget;
-use ratatui::widgets::{Block, Borders};
+use std::time::{Duration, Instant};
 
-use crate::game::prelude::Position;
+use board_widget::{BoardWidget, player_color};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::{
+    DefaultTerminal,
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
 
-pub fn draw(frame: &mut ratatui::Frame, game: &crate::game::Game, selection: Position) {
-    let board_game_block = Block::default()
-        .title(game.next_action())
+use crate::{
+    game::{clock::Clock, prelude::Position},
+    player::analysis::Analysis,
+};
+
+/// The conversation with a network opponent, shown under the board.
+pub struct Chat<'a> {
+    /// Messages of both sides, oldest first.
+    pub lines: &'a [String],
+    /// The message being written, if any.
+    pub input: Option<&'a str>,
+    /// Whether the opponent offered a draw.
+    pub draw_offered: bool,
+}
+
+pub fn draw(
+    frame: &mut ratatui::Frame,
+    game: &crate::game::Game,
+    selection: Position,
+    clock: Option<&Clock>,
+    analysis: Option<&Analysis>,
+    chat: Option<&Chat>,
+) {
+    let (area, panel) = split_clock(frame.area(), clock.is_some());
+    if let (Some(clock), Some(panel)) = (clock, panel) {
+        draw_clock(frame, game, clock, panel);
+    }
+    let (area, panel) = split_chat(area, chat.is_some());
+    if let (Some(chat), Some(panel)) = (chat, panel) {
+        draw_chat(frame, chat, panel);
+    }
+    let title = match analysis {
+        None => game.next_action(),
+        Some(analysis) if analysis.running() => {
+            format!(
+                "{} - analysing, press any key to cancel",
+                game.next_action()
+            )
+        }
+        Some(analysis) => match analysis.hint() {
+            Some(hint) => format!("{} - hint: {hint}", game.next_action()),
+            None => format!("{} - no hint", game.next_action()),
+        },
+    };
+    let hint = analysis.and_then(Analysis::hint).map(|hint| &hint.turn);
+    draw_board(frame, game, Some(selection), hint, title, area);
+}
+
+// The board area and, in timed games, the clock panel next to it.
+fn split_clock(area: Rect, timed: bool) -> (Rect, Option<Rect>) {
+    if !timed {
+        return (area, None);
+    }
+    let [board, panel] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(22)]).areas(area);
+    (board, Some(panel))
+}
+
+// The board area and, in network games, the chat panel under it.
+fn split_chat(area: Rect, networked: bool) -> (Rect, Option<Rect>) {
+    if !networked {
+        return (area, None);
+    }
+    let [board, panel] = Layout::vertical([Constraint::Min(0), Constraint::Length(7)]).areas(area);
+    (board, Some(panel))
+}
+
+/// The tile clicked at `column` and `row` in a frame of `area` drawn by `draw`.
+pub fn position_at(
+    area: Rect,
+    game: &crate::game::Game,
+    timed: bool,
+    networked: bool,
+    column: u16,
+    row: u16,
+) -> Option<Position> {
+    let (board, _) = split_clock(area, timed);
+    let (board, _) = split_chat(board, networked);
+    let board = Block::default().borders(Borders::ALL).inner(board);
+    board_widget::position_at(board, game.board().size, column, row)
+}
+
+/// Sets up the terminal like `ratatui::init`, also reporting mouse clicks.
+pub fn init() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    let _ = crossterm::execute!(std::io::stdout(), EnableMouseCapture);
+    terminal
+}
+
+pub fn restore() {
+    let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+}
+
+pub fn draw_replay(frame: &mut ratatui::Frame, game: &crate::game::Game, title: &str) {
+    draw_board(frame, game, None, None, title.to_string(), frame.area());
+}
+
+fn draw_board(
+    frame: &mut ratatui::Frame,
+    game: &crate::game::Game,
+    selection: Option<Position>,
+    hint: Option<&crate::game::turn::Turn>,
+    title: String,
+    area: Rect,
+) {
+    let board_game_block = Block::default().title(title).borders(Borders::ALL);
+    let board_area = board_game_block.inner(area);
+
+    frame.render_widget(board_game_block, area);
+    frame.render_widget(BoardWidget(game, selection, hint), board_area);
+}
+
+fn draw_clock(frame: &mut ratatui::Frame, game: &crate::game::Game, clock: &Clock, area: Rect) {
+    let now = Instant::now();
+    let board = game.board();
+    let lines: Vec<Line> = board
+        .players()
+        .map(|player| {
+            let style = if player == board.current_player() && game.outcome().is_none() {
+                Style::from(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(
+                    player.to_string(),
+                    Style::default().fg(player_color(player)),
+                ),
+                Span::styled(
+                    format!(" {:>8}", format_time(clock.remaining(player, now))),
+                    style,
+                ),
+            ])
+        })
+        .collect();
+    let block = Block::default()
+        .title(format!("Clock {}", clock.control))
         .borders(Borders::ALL);
-    let board_area = board_game_block.inner(frame.area());
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_chat(frame: &mut ratatui::Frame, chat: &Chat, area: Rect) {
+    let title = if chat.draw_offered {
+        "Chat - draw offered, d to accept, x to resign"
+    } else {
+        "Chat - c to write, d to offer a draw, x to resign"
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    // The latest lines that fit, leaving room for the message being written.
+    let height = usize::from(block.inner(area).height);
+    let shown = height.saturating_sub(usize::from(chat.input.is_some()));
+    let mut lines: Vec<Line> = chat.lines[chat.lines.len().saturating_sub(shown)..]
+        .iter()
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    if let Some(input) = chat.input {
+        lines.push(Line::from(format!("> {input}_")).style(Modifier::BOLD));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
 
-    frame.render_widget(board_game_block, frame.area());
-    frame.render_widget(BoardWidget(game, selection), board_area);
+// `m:ss`, with tenths of seconds under ten seconds.
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds < 10 {
+        format!("{seconds}.{}", time.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
 }